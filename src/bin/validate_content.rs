@@ -0,0 +1,21 @@
+use std::{env, process};
+
+use sleeping_gods::{check_content, DEFAULT_CONTENT_DIR};
+
+// Usage: validate-content [content dir]
+fn main() {
+    let dir = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_CONTENT_DIR.to_owned());
+
+    let errors = check_content(&dir);
+    if errors.is_empty() {
+        println!("{} is valid", dir);
+    } else {
+        for error in errors.iter() {
+            eprintln!("{}", error);
+        }
+        eprintln!("{} problems found in {}", errors.len(), dir);
+        process::exit(1);
+    }
+}
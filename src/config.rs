@@ -0,0 +1,43 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{
+    game_state::content::DEFAULT_CONTENT_DIR, store::StoreConfig,
+};
+
+pub const CONFIG_PATH: &str = "server_config.json";
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub address: String,
+    pub store: StoreConfig,
+    pub content_dir: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            address: "localhost:2000".to_owned(),
+            store: StoreConfig::default(),
+            content_dir: DEFAULT_CONTENT_DIR.to_owned(),
+        }
+    }
+}
+
+impl ServerConfig {
+    // A missing config file just means the defaults are used
+    pub fn load(path: &str) -> Result<ServerConfig, String> {
+        if !Path::new(path).exists() {
+            return Ok(ServerConfig::default());
+        }
+
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid {}: {}", path, e))
+            })
+    }
+}
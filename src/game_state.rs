@@ -1,22 +1,38 @@
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
 use serde::{Deserialize, Serialize, Serializer};
 pub mod ability_card_deck;
 pub mod action;
+pub mod adventure_deck;
+pub mod campaign;
 pub mod challenge;
 pub mod client_message;
+pub mod content;
 pub mod crew;
 pub mod deck;
+pub mod effect;
 pub mod event_deck;
 pub mod game_phase;
+pub mod game_setup;
+pub mod locale;
 pub mod map;
+pub mod pack;
 pub mod player;
+pub mod save;
+mod script;
 pub mod skill;
+pub mod transcript;
 
 use self::{
-    ability_card_deck::ability_card_deck, event_deck::event_deck,
-    map::{GameMap, MapData}, map::SerialMap
+    content::Content,
+    effect::{apply_effects, Effect, EffectContext},
+    locale::message,
+    map::{GameMap, SerialMap},
+    pack::PackRef,
 };
-use ability_card_deck::AbilityCard;
-use challenge::Challenge;
+use ability_card_deck::{ability_card_deck, AbilityCard};
+use adventure_deck::{AdventureCard, MARKET_SIZE};
+use challenge::{fate_deck, Challenge, FateCard};
 use client_message::ClientMessage;
 use crew::Crew;
 use deck::Deck;
@@ -38,9 +54,36 @@ pub struct GameState {
     #[serde(serialize_with = "serialize_map")]
     map: GameMap,
 
+    // The room taken this turn, and the one taken last turn which
+    // can't be taken again
     room: ShipRoom,
+    previous_room: ShipRoom,
+    // Both start at 1. A round ends when every player has had a turn.
+    turn: u32,
+    round: u32,
+    active_player: usize,
     resources: Resources,
+    // Damage the ship took instead of the crew
+    ship_damage: u32,
+    // Adventure cards for sale, and the ones bought
+    market: Vec<AdventureCard>,
+    adventure_cards: Vec<AdventureCard>,
+    // The content packs this game is played with
+    packs: Vec<PackRef>,
+    #[serde(serialize_with = "serialize_messages")]
     message_queue: Vec<ClientMessage>,
+    // Everything that happened since the last action, for the
+    // transcript
+    #[serde(skip_serializing)]
+    events: Vec<ClientMessage>,
+
+    // Every shuffle goes through this, so a game can be replayed
+    // from its seed. Clients could work out the decks from it, so
+    // only the campaign save keeps it.
+    #[serde(skip_serializing)]
+    seed: u64,
+    #[serde(skip_serializing)]
+    rng: GameRng,
 
     #[serde(skip_serializing)]
     ability_deck: Deck<AbilityCard>,
@@ -48,6 +91,10 @@ pub struct GameState {
     search_token_deck: Deck<SearchToken>,
     #[serde(skip_serializing)]
     event_card_deck: Deck<EventCard>,
+    #[serde(skip_serializing)]
+    adventure_deck: Deck<AdventureCard>,
+    #[serde(skip_serializing)]
+    fate_deck: Deck<FateCard>,
 }
 
 // Serializers
@@ -66,34 +113,93 @@ where S: Serializer {
     SerialMap::from(map.clone()).serialize(ser)
 }
 
+// Each message goes out with its text, which is the only part the
+// server translates
+fn serialize_messages<S>(
+    messages: &[ClientMessage],
+    ser: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    ser.collect_seq(messages.iter().map(|msg| {
+        serde_json::json!({
+            "message": msg,
+            "text": msg.to_string(),
+        })
+    }))
+}
+
 
 
 // Impl
 impl GameState {
+    #[cfg(test)]
     pub fn init_state() -> GameState {
-        GameState {
+        let content =
+            Content::load(content::DEFAULT_CONTENT_DIR, &[]).unwrap();
+        GameState::new(&content, 0)
+    }
+
+    // Knocks out the whole crew
+    #[cfg(test)]
+    pub fn lose_game(self) -> GameState {
+        let mut gs = self;
+        for crew in gs.crew.iter_mut() {
+            crew.damage = crew.max_health;
+        }
+        gs.check_game_over().unwrap()
+    }
+
+    pub fn new(content: &Content, seed: u64) -> GameState {
+        let mut rng = GameRng::seed_from_u64(seed);
+
+        let gs = GameState {
             phase_stack: vec![GamePhase::ShipActionPhase(None)],
             players: vec![Player::default()],
-            crew: vec![
-                Crew::new("Rafael Vieira", 0, 1, 1, 0, 0),
-                Crew::new("Audrie Williams", 0, 1, 0, 0, 1),
-                Crew::new("Katsumi Aoshima", 1, 0, 0, 1, 1),
-                Crew::new("Kannan Sharma", 1, 0, 1, 1, 0),
-                Crew::new("Sofi Odessa", 1, 1, 1, 1, 1),
-                Crew::new("Gregory Little", 1, 0, 1, 0, 0),
-                Crew::new("Laurant Lapointe", 1, 1, 1, 0, 1),
-                Crew::new("Marco Reyes", 0, 0, 1, 1, 0),
-            ],
-            map: GameMap {ship_area: 1, map_data: MapData::default()},
+            crew: content.starting_crew(),
+            map: GameMap::new(&content.map),
             room: ShipRoom::None,
+            previous_room: ShipRoom::None,
+            turn: 1,
+            round: 1,
+            active_player: 0,
             resources: Resources::default(),
-            ability_deck: Deck::new(ability_card_deck()),
+            ship_damage: 0,
+            packs: content.pack_refs(),
+            ability_deck: Deck::new(
+                ability_card_deck(&content.ability_cards),
+                &mut rng,
+            ),
             search_token_deck: Deck::new(
-                (1..8).map(SearchToken).collect(),
+                content.search_tokens.clone(),
+                &mut rng,
+            ),
+            event_card_deck: Deck::new(
+                content.event_cards.clone(),
+                &mut rng,
+            ),
+            adventure_deck: Deck::new(
+                content.adventure_cards.clone(),
+                &mut rng,
             ),
-            event_card_deck: Deck::new(event_deck()),
+            fate_deck: Deck::new(
+                fate_deck(&content.fate_cards),
+                &mut rng,
+            ),
+            market: Vec::new(),
+            adventure_cards: Vec::new(),
             message_queue: Vec::new(),
-        }
+            events: Vec::new(),
+            seed,
+            rng,
+        };
+        gs.refill_market()
+    }
+
+    // Replaces the starting crew with the one chosen at setup
+    pub fn with_crew(self, crew: Vec<Crew>) -> GameState {
+        GameState { crew, ..self }
     }
 
     fn phase(&self) -> GamePhase {
@@ -125,11 +231,64 @@ impl GameState {
         Ok(self.clone()).map(|g| {
             g.push_phase(GamePhase::ChallengePhase {
                 challenge,
-                added: None,
+                result: None,
+                boost: 0,
+                crew: Vec::new(),
+                total: 0,
             })
         })
     }
 
+    // Every change to a challenge before it's resolved goes through
+    // here to keep the total up to date
+    fn update_challenge(
+        &self,
+        update: impl FnOnce(
+            &mut Vec<usize>,
+            &mut u32,
+        ) -> Result<(), String>,
+    ) -> Update {
+        if let GamePhase::ChallengePhase {
+            challenge,
+            result: None,
+            mut boost,
+            mut crew,
+            ..
+        } = self.phase()
+        {
+            update(&mut crew, &mut boost)?;
+            let total = self
+                .crew_skill(&crew, challenge.skill)
+                .checked_add(boost)
+                .ok_or("error.boost_too_large")?;
+            self.set_phase(GamePhase::ChallengePhase {
+                challenge,
+                result: None,
+                boost,
+                crew,
+                total,
+            })
+        } else {
+            Err("error.no_challenge".to_owned())
+        }
+    }
+
+    fn boost_challenge(&self, amount: u32) -> Update {
+        self.update_challenge(|_, boost| {
+            *boost = boost
+                .checked_add(amount)
+                .ok_or("error.boost_too_large")?;
+            Ok(())
+        })
+    }
+
+    fn crew_skill(&self, crew: &[usize], skill: Skill) -> u32 {
+        crew.iter()
+            .filter_map(|ix| self.crew.get(*ix))
+            .map(|c| c.contribution(skill))
+            .sum()
+    }
+
     fn give_command_tokens(
         self,
         player_ix: usize,
@@ -144,23 +303,228 @@ impl GameState {
                 })
             })
         } else {
-            Err("Player does not exist".to_owned())
+            Err("error.no_player".to_owned())
         }
     }
 
-    fn apply_search_tokens(self, _token: &SearchToken) -> Update {
+    fn spend_command_tokens(
+        self,
+        player_ix: usize,
+        amount: u32,
+    ) -> Update {
         let mut gs = self.clone();
-        gs.resources.meat += 1;
+        let player = gs
+            .players
+            .get_mut(player_ix)
+            .ok_or("error.no_player".to_owned())?;
+        if player.command_tokens < amount {
+            return Err(message(
+                "error.not_enough_tokens",
+                &[&amount],
+            ));
+        }
+
+        player.command_tokens -= amount;
         Ok(gs)
     }
 
+    fn gain_resource(
+        self,
+        resource: Resource,
+        amount: u32,
+    ) -> Update {
+        let mut gs = self.clone();
+        *gs.resources.get_mut(resource) += amount;
+        Ok(gs).and_then(|g| {
+            g.log_event(ClientMessage::GainedResource {
+                resource,
+                amount,
+            })
+        })
+    }
+
+    // Unlike losing, paying needs the full amount
+    fn pay(self, resource: Resource, amount: u32) -> Update {
+        if self.resources.get(resource) < amount {
+            let resource = format!("resource.{:?}", resource);
+            return Err(message("error.not_enough", &[&resource]));
+        }
+        self.lose_resource(resource, amount)
+    }
+
+    // Lays out cards until the market is full or the deck runs out
+    fn refill_market(mut self) -> GameState {
+        while self.market.len() < MARKET_SIZE {
+            match self.adventure_deck.draw(&mut self.rng) {
+                Ok(card) => self.market.push(card),
+                Err(_) => break,
+            }
+        }
+        self
+    }
+
+    fn buy_adventure_card(self, market_ix: usize) -> Update {
+        let card = self
+            .market
+            .get(market_ix)
+            .cloned()
+            .ok_or("error.no_market_card".to_owned())?;
+
+        let mut gs = self.pay(Resource::Coins, card.cost)?;
+        gs.market.remove(market_ix);
+        gs.adventure_cards.push(card.clone());
+        gs.refill_market().queue_message(
+            ClientMessage::BoughtAdventureCard { card },
+        )
+    }
+
+    // Loses as much as there is
+    fn lose_resource(
+        self,
+        resource: Resource,
+        amount: u32,
+    ) -> Update {
+        let mut gs = self.clone();
+        let owned = gs.resources.get_mut(resource);
+        let amount = amount.min(*owned);
+        *owned -= amount;
+        Ok(gs).and_then(|g| {
+            g.log_event(ClientMessage::LostResource {
+                resource,
+                amount,
+            })
+        })
+    }
+
+    // The players decide who takes the damage before play goes on
+    fn take_damage(self, damage: u8, ship: bool) -> Update {
+        let anyone =
+            ship || self.crew.iter().any(|c| !c.is_knocked_out());
+        if damage == 0 || !anyone {
+            return Ok(self);
+        }
+
+        Ok(self.push_phase(GamePhase::DamagePhase {
+            amount: damage,
+            ship,
+        }))
+    }
+
+    fn damage_crew(self, crew_ix: usize, amount: u8) -> Update {
+        let mut gs = self.clone();
+        let crew = gs
+            .crew
+            .get_mut(crew_ix)
+            .ok_or("error.no_crew".to_owned())?;
+        let was_down = crew.is_knocked_out();
+        crew.take_damage(amount);
+        let knocked_out = !was_down && crew.is_knocked_out();
+
+        let crew = crew.name.clone();
+        let gs = gs.log_event(ClientMessage::TookDamage {
+            crew: crew.clone(),
+            amount,
+        })?;
+        if knocked_out {
+            gs.queue_message(ClientMessage::KnockedOut { crew })
+        } else {
+            Ok(gs)
+        }
+    }
+
+    // Ends the game once the whole crew or the captain is down
+    fn check_game_over(self) -> Update {
+        let reason = if self.crew.iter().all(|c| c.is_knocked_out()) {
+            "game_over.crew_down"
+        } else if self
+            .crew
+            .iter()
+            .any(|c| c.captain && c.is_knocked_out())
+        {
+            "game_over.captain_down"
+        } else {
+            return Ok(self);
+        };
+
+        let mut gs = self.clone();
+        gs.phase_stack = vec![GamePhase::GameOver {
+            reason: reason.to_owned(),
+        }];
+        gs.queue_message(ClientMessage::GameOver {
+            reason: reason.to_owned(),
+        })
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        matches!(self.phase(), GamePhase::GameOver { .. })
+    }
+
+    fn damage_ship(self, amount: u8) -> Update {
+        let mut gs = self.clone();
+        gs.ship_damage += u32::from(amount);
+        Ok(gs).and_then(|g| {
+            g.log_event(ClientMessage::ShipDamaged { amount })
+        })
+    }
+
+    fn heal(self, crew_ix: usize, amount: u8) -> Update {
+        let mut gs = self.clone();
+        let crew = gs
+            .crew
+            .get_mut(crew_ix)
+            .ok_or("error.no_crew".to_owned())?;
+        let was_down = crew.is_knocked_out();
+        crew.heal(amount);
+        let recovered = was_down && !crew.is_knocked_out();
+
+        let crew = crew.name.clone();
+        let gs = gs.log_event(ClientMessage::Healed {
+            crew: crew.clone(),
+            amount,
+        })?;
+        if recovered {
+            gs.queue_message(ClientMessage::Recovered { crew })
+        } else {
+            Ok(gs)
+        }
+    }
+
+    fn change_fatigue(self, crew_ix: usize, amount: i32) -> Update {
+        let mut gs = self.clone();
+        let crew = gs
+            .crew
+            .get_mut(crew_ix)
+            .ok_or("error.no_crew".to_owned())?;
+        crew.change_fatigue(amount);
+
+        let crew = crew.name.clone();
+        Ok(gs).and_then(|g| {
+            g.log_event(ClientMessage::FatigueChanged {
+                crew,
+                amount,
+            })
+        })
+    }
+
+    fn apply_search_tokens(
+        self,
+        token: &SearchToken,
+        player_ix: usize,
+    ) -> Update {
+        apply_effects(
+            &token.effects,
+            &self,
+            &EffectContext::player(player_ix),
+        )
+    }
+
     fn draw_cards(self, player_ix: usize, amount: u32) -> Update {
         let mut gs = self.clone();
 
         let mut messages = Vec::new();
         if let Some(player) = gs.players.get_mut(player_ix) {
             for _ in 0..amount {
-                if let Ok(card) = gs.ability_deck.draw() {
+                if let Ok(card) = gs.ability_deck.draw(&mut gs.rng) {
                     messages.push(ClientMessage::DrewAbilityCard {
                         card: card.clone(),
                     });
@@ -169,10 +533,60 @@ impl GameState {
             }
         }
 
+        gs.events.extend(messages.iter().cloned());
         gs.message_queue.append(&mut messages);
         Ok(gs)
     }
 
+    // Passes the turn to the next player and clears what only
+    // lasts a turn
+    fn end_turn(self) -> Update {
+        let mut gs = self.clone();
+        gs.previous_room =
+            std::mem::replace(&mut gs.room, ShipRoom::None);
+        gs.turn += 1;
+        gs.active_player = (gs.active_player + 1) % gs.players.len();
+        let round_ended = gs.active_player == 0;
+        if round_ended {
+            gs.round += 1;
+        }
+
+        let gs = gs.queue_message(ClientMessage::TurnEnded {
+            turn: self.turn,
+        })?;
+        if round_ended {
+            gs.queue_message(ClientMessage::RoundEnded {
+                round: self.round,
+            })
+            .and_then(|g| g.feed_crew())
+        } else {
+            Ok(gs)
+        }
+    }
+
+    // Upkeep at the end of each round: the crew eats one provision,
+    // grain before meat. Without any, everyone still standing gets
+    // tired.
+    fn feed_crew(self) -> Update {
+        let provision = [Resource::Grain, Resource::Meat]
+            .into_iter()
+            .find(|r| self.resources.get(*r) > 0);
+
+        match provision {
+            Some(resource) => self.lose_resource(resource, 1),
+            None => {
+                let hungry: Vec<usize> = (0..self.crew.len())
+                    .filter(|ix| !self.crew[*ix].is_knocked_out())
+                    .collect();
+                let gs =
+                    self.queue_message(ClientMessage::WentHungry)?;
+                hungry
+                    .into_iter()
+                    .try_fold(gs, |g, ix| g.change_fatigue(ix, 1))
+            }
+        }
+    }
+
     fn set_room(self, room: &ShipRoom) -> Update {
         let mut gs = self.clone();
         gs.room = room.clone();
@@ -185,7 +599,7 @@ impl GameState {
         card_ix: usize,
     ) -> Update {
         if player_ix >= self.players.len() {
-            Err("".to_owned())
+            Err("error.no_player".to_owned())
         } else {
             let mut gs = self.clone();
             let player = &gs.players[player_ix];
@@ -203,10 +617,23 @@ impl GameState {
 
     fn queue_message(self, msg: ClientMessage) -> Update {
         let mut gs = self.clone();
+        gs.events.push(msg.clone());
         gs.message_queue.push(msg);
         Ok(gs)
     }
 
+    // Records an event for the transcript without showing it to the
+    // players as a message
+    fn log_event(self, msg: ClientMessage) -> Update {
+        let mut gs = self.clone();
+        gs.events.push(msg);
+        Ok(gs)
+    }
+
+    pub fn take_events(&mut self) -> Vec<ClientMessage> {
+        std::mem::take(&mut self.events)
+    }
+
     fn dequeue_message(self) -> Update {
         let mut gs = self.clone();
         gs.message_queue.pop();
@@ -215,31 +642,71 @@ impl GameState {
         Ok(gs)
     }
 
+    // Card effects can move the ship anywhere on the map, travel
+    // checks the path before this
     fn move_ship(self, to_area: u32) -> Update {
+        if !self.map.map_data.has_area(to_area) {
+            return Err(message("error.unknown_area", &[&to_area]));
+        }
+
         let mut gs = self.clone();
         gs.map.ship_area = to_area;
-        Ok(gs)
+        Ok(gs).and_then(|g| {
+            g.queue_message(ClientMessage::ShipMoved { to_area })
+        })
     }
 }
 
-#[derive(Clone, Serialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct Resources {
     coins: u32,
     grain: u32,
     meat: u32,
 }
 
-#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+impl Resources {
+    fn get(&self, resource: Resource) -> u32 {
+        match resource {
+            Resource::Coins => self.coins,
+            Resource::Grain => self.grain,
+            Resource::Meat => self.meat,
+        }
+    }
+
+    fn get_mut(&mut self, resource: Resource) -> &mut u32 {
+        match resource {
+            Resource::Coins => &mut self.coins,
+            Resource::Grain => &mut self.grain,
+            Resource::Meat => &mut self.meat,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
+pub enum Resource {
+    Coins,
+    Grain,
+    Meat,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
 pub enum ShipRoom {
     Galley,
     Bridge,
     Deck,
     Quarters,
     Sickbay,
+    #[default]
     None,
 }
 
-#[derive(Clone, Serialize, Copy, Default)]
-pub struct SearchToken(u32);
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct SearchToken {
+    id: u32,
+    #[serde(skip_serializing)]
+    effects: Vec<Effect>,
+}
 
 type Update = Result<GameState, String>;
+
+pub type GameRng = Pcg32;
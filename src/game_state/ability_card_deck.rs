@@ -1,21 +1,105 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Serialize, Default)]
+use super::{
+    effect::{validate_effects, Effect},
+    game_phase::GamePhase,
+    skill::Skill,
+};
+
+#[derive(
+    Clone, Serialize, Deserialize, Default, PartialEq, Debug,
+)]
 pub struct AbilityCard {
-    name: String,
-    deck_ix: u32
+    pub name: String,
+    pub deck_ix: u32,
+    #[serde(default)]
+    pub timing: Timing,
+    // Counted when the card is drawn for a challenge
+    #[serde(default)]
+    pub icons: Vec<Skill>,
+    // Saves only keep the name and index, the effects always come
+    // from the deck
+    #[serde(default, skip_serializing)]
+    pub effects: Vec<Effect>,
+}
+
+// When a card may be played
+#[derive(
+    Clone, Copy, Serialize, Deserialize, Default, PartialEq, Debug,
+)]
+pub enum Timing {
+    #[default]
+    Anytime,
+    // Only while a challenge is waiting to be resolved
+    Challenge,
+}
+
+// An entry in a pack's ability_cards.json
+#[derive(Clone, Deserialize)]
+pub struct AbilityCardEntry {
+    #[serde(flatten)]
+    pub card: AbilityCard,
+    // How many copies are shuffled into the deck
+    #[serde(default = "one")]
+    pub count: u32,
+}
+
+pub(super) fn one() -> u32 {
+    1
 }
 
 impl AbilityCard {
-    fn new(name: &str, deck_ix: u32) -> Self {
-        AbilityCard { name: name.to_owned(), deck_ix }
+    pub fn can_play(&self, phase: &GamePhase) -> bool {
+        let in_challenge = matches!(
+            phase,
+            GamePhase::ChallengePhase { result: None, .. }
+        );
+
+        match self.timing {
+            Timing::Anytime => true,
+            Timing::Challenge => in_challenge,
+        }
     }
 }
 
-pub fn ability_card_deck() -> Vec<AbilityCard> {
-    let card1 = AbilityCard::new("Triage", 1);
-    let card2 = AbilityCard::new("Focused Mind", 2);
-    let card3 = AbilityCard::new("Counsel", 3);
+// Every copy of every card, ready to be shuffled
+pub fn ability_card_deck(
+    entries: &[AbilityCardEntry],
+) -> Vec<AbilityCard> {
+    entries
+        .iter()
+        .flat_map(|e| (0..e.count).map(|_| e.card.clone()))
+        .collect()
+}
+
+pub fn validate_ability_cards(
+    entries: &[AbilityCardEntry],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (ix, entry) in entries.iter().enumerate() {
+        let card = &entry.card;
+        if entries[..ix]
+            .iter()
+            .any(|e| e.card.deck_ix == card.deck_ix)
+        {
+            errors.push(format!(
+                "Ability card {} reuses deck index {}",
+                card.name, card.deck_ix
+            ));
+        }
+        if entry.count == 0 {
+            errors.push(format!(
+                "Ability card {} has a count of 0",
+                card.name
+            ));
+        }
+        errors.extend(validate_effects(
+            &card.effects,
+            &format!("Ability card {}", card.name),
+            true,
+        ));
+    }
 
-    vec![card1, card2, card3]
+    errors
 }
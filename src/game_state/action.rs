@@ -8,13 +8,25 @@ use serde_json::{Value};
 mod accept_challenge_result_action;
 mod accept_message_action;
 mod choose_token_for_deck_action;
+mod commit_ability_card_action;
+mod commit_crew_action;
+mod distribute_damage_action;
 mod draw_for_deck_action;
+mod end_main_action_phase;
+mod end_turn;
+mod finish_main_action;
 mod handle_event_phase_action;
+mod play_ability_card_action;
 mod resolve_challenge_action;
+mod select_choice_action;
+mod select_crew_for_quarters_action;
+mod select_crew_for_sickbay_action;
 mod select_discard_for_galley_action;
 mod select_event_option_action;
 mod take_ship_action;
+mod town_action;
 mod select_main_action;
+mod spend_command_tokens_action;
 mod travel_action;
 
 #[typetag::serde(tag = "actionType", content = "actionData")]
@@ -22,6 +34,11 @@ pub trait Action: fmt::Display {
     fn execute(&self, state: &GameState) -> Update {
         Ok(state.clone())
     }
+
+    // Whether the action can still be taken once the game is over
+    fn after_game_over(&self) -> bool {
+        false
+    }
 }
 
 pub fn get_action(action_msg_str: &str) -> Box<dyn Action>{
@@ -17,13 +17,13 @@ impl Action for AcceptChallengeResultAction {
     fn execute(&self, state: &crate::game_state::GameState) -> crate::game_state::Update {
         
         if let GamePhase::ChallengePhase {
-            challenge: _,
-            added: Some(_),
+            result: Some(_),
+            ..
         } = state.phase() {
             Ok(state.clone())
                 .and_then(|g| g.pop_phase())
         } else {
-            Err("Wrong phase".to_owned())
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
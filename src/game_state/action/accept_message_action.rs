@@ -20,6 +20,12 @@ impl Action for AcceptMessageAction {
     ) -> Update {
         Ok(state.clone()).and_then(|g| g.dequeue_message())
     }
+
+    // The players still have to dismiss the messages saying how the
+    // game ended
+    fn after_game_over(&self) -> bool {
+        true
+    }
 }
 
 impl Display for AcceptMessageAction {
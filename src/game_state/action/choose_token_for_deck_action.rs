@@ -3,13 +3,13 @@ use std::fmt::Display;
 use serde::{Deserialize, Serialize};
 
 use super::Action;
+use crate::game_state::client_message::ClientMessage;
 use crate::game_state::game_phase::ShipActionSubphase;
 use crate::game_state::{GamePhase, GameState, SearchToken, Update};
 
 #[derive(Deserialize, Serialize)]
 pub struct ChooseTokenForDeckAction {
     token_id: u32,
-    #[allow(dead_code)]
     player_ix: usize,
 }
 
@@ -24,22 +24,24 @@ impl Action for ChooseTokenForDeckAction {
             )) = state.phase()
             {
                 if search_tokens_drawn.is_empty() {
-                    Err("You must draw at least 1 token".to_owned())
+                    Err("error.min_tokens".to_owned())
                 } else {
                     let (tokens, discards): (
                         Vec<SearchToken>,
                         Vec<SearchToken>,
-                    ) = search_tokens_drawn.clone().iter().partition(
-                        |SearchToken(id)| *id == self.token_id,
-                    );
+                    ) = search_tokens_drawn
+                        .clone()
+                        .into_iter()
+                        .partition(|token| token.id == self.token_id);
 
                     tokens
                         .first()
-                        .ok_or("Couldn't find token id".to_owned())
-                        .map(|t| (*t, discards))
+                        .cloned()
+                        .ok_or("error.unknown_token".to_owned())
+                        .map(|t| (t, discards))
                 }
             } else {
-                Err("wrong phase".to_owned())
+                Err("error.wrong_phase".to_owned())
             };
 
         t.and_then(|(token, discards)| {
@@ -52,7 +54,14 @@ impl Action for ChooseTokenForDeckAction {
                 .and_then(|g| {
                     g.set_phase(GamePhase::EventPhase(None))
                 })
-                .and_then(|g| g.apply_search_tokens(&token))
+                .and_then(|g| {
+                    g.log_event(ClientMessage::ChoseSearchToken {
+                        token: token.clone(),
+                    })
+                })
+                .and_then(|g| {
+                    g.apply_search_tokens(&token, self.player_ix)
+                })
         })
     }
 }
@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    client_message::ClientMessage, locale::message, GamePhase,
+    GameState, Update,
+};
+
+// Discards a card from hand for its icons matching the challenge
+// skill, instead of playing it for its effects
+#[derive(Deserialize, Serialize)]
+pub struct CommitAbilityCardAction {
+    card_ix: usize,
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "commitAbilityCardAction")]
+impl Action for CommitAbilityCardAction {
+    fn execute(&self, state: &GameState) -> Update {
+        let skill = match state.phase() {
+            GamePhase::ChallengePhase {
+                challenge,
+                result: None,
+                ..
+            } => challenge.skill,
+            _ => return Err("error.no_challenge".to_owned()),
+        };
+        let card = state
+            .players
+            .get(self.player_ix)
+            .and_then(|p| p.hand.get(self.card_ix))
+            .ok_or("error.no_card")?
+            .clone();
+
+        let amount =
+            card.icons.iter().filter(|icon| **icon == skill).count()
+                as u32;
+        if amount == 0 {
+            return Err(message(
+                "error.no_matching_icon",
+                &[&card.name, &format!("skill.{}", skill)],
+            ));
+        }
+
+        state
+            .clone()
+            .discard_card(self.player_ix, self.card_ix)
+            .and_then(|g| g.boost_challenge(amount))
+            .and_then(|g| {
+                g.log_event(ClientMessage::CommittedAbilityCard {
+                    card,
+                    amount,
+                })
+            })
+    }
+}
+
+impl Display for CommitAbilityCardAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Commit Ability Card Action\n{}\n{}",
+            self.card_ix, self.player_ix
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{
+        challenge::Challenge, skill::Skill, AbilityCard,
+    };
+
+    fn with_card(icons: Vec<Skill>) -> GameState {
+        let mut gs = GameState::init_state()
+            .challenge(Challenge {
+                skill: Skill::Savvy,
+                ..Challenge::default()
+            })
+            .unwrap();
+        gs.players[0].add_card(AbilityCard {
+            icons,
+            ..AbilityCard::default()
+        });
+        gs
+    }
+
+    #[test]
+    fn test_matching_icons_boost() {
+        let gs = with_card(vec![
+            Skill::Savvy,
+            Skill::Savvy,
+            Skill::Strength,
+        ]);
+        let action = CommitAbilityCardAction {
+            card_ix: 0,
+            player_ix: 0,
+        };
+
+        let gs = action.execute(&gs).unwrap();
+        assert!(gs.players[0].hand.is_empty());
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ChallengePhase {
+                boost: 2,
+                total: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_err_without_matching_icon() {
+        let gs = with_card(vec![Skill::Strength]);
+        let action = CommitAbilityCardAction {
+            card_ix: 0,
+            player_ix: 0,
+        };
+
+        assert!(action.execute(&gs).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{locale::message, GameState, Update};
+
+// Adds a crew member to the challenge, or takes them back out
+#[derive(Deserialize, Serialize)]
+pub struct CommitCrewAction {
+    crew_ix: usize,
+    #[serde(default)]
+    remove: bool,
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "commitCrewAction")]
+impl Action for CommitCrewAction {
+    fn execute(&self, state: &GameState) -> Update {
+        let member =
+            state.crew.get(self.crew_ix).ok_or("error.no_crew")?;
+        // Taking someone out is always allowed
+        if !self.remove && member.is_knocked_out() {
+            return Err(message(
+                "error.crew_knocked_out",
+                &[&member.name],
+            ));
+        }
+        if !self.remove && member.is_exhausted() {
+            return Err(message(
+                "error.crew_exhausted",
+                &[&member.name],
+            ));
+        }
+
+        state.update_challenge(|crew, _| {
+            let pos = crew.iter().position(|ix| *ix == self.crew_ix);
+            match (pos, self.remove) {
+                (None, false) => crew.push(self.crew_ix),
+                (Some(pos), true) => {
+                    crew.remove(pos);
+                }
+                (Some(_), false) => {
+                    return Err(
+                        "error.crew_already_committed".to_owned()
+                    )
+                }
+                (None, true) => {
+                    return Err("error.crew_not_committed".to_owned())
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Display for CommitCrewAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Commit Crew Action\n{}\n{}",
+            self.crew_ix, self.remove
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{
+        challenge::Challenge, skill::Skill, GamePhase,
+    };
+
+    fn in_challenge() -> GameState {
+        GameState::init_state()
+            .challenge(Challenge {
+                skill: Skill::Savvy,
+                amount: 5,
+                ..Challenge::default()
+            })
+            .unwrap()
+    }
+
+    fn commit(crew_ix: usize, remove: bool) -> CommitCrewAction {
+        CommitCrewAction {
+            crew_ix,
+            remove,
+            player_ix: 0,
+        }
+    }
+
+    #[test]
+    fn test_total_follows_crew() {
+        let gs = in_challenge();
+        let skill = gs.crew[2].skill(Skill::Savvy);
+
+        let gs = commit(2, false).execute(&gs).unwrap();
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ChallengePhase { crew, total, .. }
+                if crew == vec![2] && total == skill
+        ));
+
+        let gs = commit(2, true).execute(&gs).unwrap();
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ChallengePhase { crew, total: 0, .. }
+                if crew.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_err_if_committed_twice() {
+        let gs = commit(2, false).execute(&in_challenge()).unwrap();
+
+        assert_eq!(
+            commit(2, false).execute(&gs).err(),
+            Some("error.crew_already_committed".to_owned())
+        );
+        assert_eq!(
+            commit(3, true).execute(&gs).err(),
+            Some("error.crew_not_committed".to_owned())
+        );
+        assert!(commit(1000, false).execute(&gs).is_err());
+    }
+
+    #[test]
+    fn test_err_if_exhausted_or_knocked_out() {
+        let mut gs = in_challenge();
+        gs.crew[2].fatigue = 2;
+        gs.crew[3].damage = gs.crew[3].max_health;
+
+        assert!(commit(2, false).execute(&gs).is_err());
+        assert!(commit(3, false).execute(&gs).is_err());
+    }
+
+    #[test]
+    fn test_fatigue_lowers_total() {
+        let mut gs = in_challenge();
+        gs.crew[2].fatigue = 1;
+        let skill = gs.crew[2].skill(Skill::Savvy);
+
+        let gs = commit(2, false).execute(&gs).unwrap();
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ChallengePhase { total, .. }
+                if total == skill.saturating_sub(1)
+        ));
+    }
+
+    #[test]
+    fn test_err_outside_challenge() {
+        let gs = GameState::init_state();
+
+        assert!(commit(0, false).execute(&gs).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    locale::message, GamePhase, GameState, Update,
+};
+
+// How the players share out the damage of a DamagePhase
+#[derive(Deserialize, Serialize)]
+pub struct DistributeDamageAction {
+    // The damage for each crew member, by index. Missing entries
+    // take none.
+    crew: Vec<u8>,
+    #[serde(default)]
+    ship: u8,
+    player_ix: usize,
+}
+
+impl DistributeDamageAction {
+    fn validate(
+        &self,
+        state: &GameState,
+        amount: u8,
+        ship: bool,
+    ) -> Update {
+        if self.crew.len() > state.crew.len() {
+            return Err("error.no_crew".to_owned());
+        }
+        if self.ship > 0 && !ship {
+            return Err("error.ship_cant_take_damage".to_owned());
+        }
+        let knocked_out =
+            self.crew.iter().zip(state.crew.iter()).find(
+                |(damage, crew)| {
+                    **damage > 0 && crew.is_knocked_out()
+                },
+            );
+        if let Some((_, crew)) = knocked_out {
+            return Err(message(
+                "error.crew_knocked_out",
+                &[&crew.name],
+            ));
+        }
+
+        let total: u32 =
+            self.crew.iter().map(|d| u32::from(*d)).sum::<u32>()
+                + u32::from(self.ship);
+        if total != u32::from(amount) {
+            return Err(message(
+                "error.damage_not_distributed",
+                &[&amount],
+            ));
+        }
+
+        Ok(state.clone())
+    }
+}
+
+#[typetag::serde(name = "distributeDamageAction")]
+impl Action for DistributeDamageAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::DamagePhase { amount, ship } = state.phase()
+        {
+            let gs = self
+                .validate(state, amount, ship)
+                .and_then(|g| g.pop_phase())?;
+
+            self.crew
+                .iter()
+                .enumerate()
+                .filter(|(_, damage)| **damage > 0)
+                .try_fold(gs, |g, (ix, damage)| {
+                    g.damage_crew(ix, *damage)
+                })
+                .and_then(|g| match self.ship {
+                    0 => Ok(g),
+                    damage => g.damage_ship(damage),
+                })
+                .and_then(|g| g.check_game_over())
+        } else {
+            Err("error.wrong_phase".to_owned())
+        }
+    }
+}
+
+impl Display for DistributeDamageAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Distribute Damage Action\n{:?}\n{}",
+            self.crew, self.ship
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn taking_damage(ship: bool) -> GameState {
+        GameState::init_state().take_damage(3, ship).unwrap()
+    }
+
+    fn distribute(crew: Vec<u8>, ship: u8) -> DistributeDamageAction {
+        DistributeDamageAction {
+            crew,
+            ship,
+            player_ix: 0,
+        }
+    }
+
+    #[test]
+    fn test_damage_waits_for_players() {
+        let gs = taking_damage(false);
+
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::DamagePhase {
+                amount: 3,
+                ship: false
+            }
+        ));
+        assert!(gs.crew.iter().all(|c| c.damage == 0));
+    }
+
+    #[test]
+    fn test_damage_shared_out() {
+        let gs = distribute(vec![1, 0, 2], 0)
+            .execute(&taking_damage(false))
+            .unwrap();
+
+        assert_eq!(gs.crew[0].damage, 1);
+        assert_eq!(gs.crew[2].damage, 2);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ShipActionPhase(None)
+        ));
+    }
+
+    #[test]
+    fn test_ship_takes_damage() {
+        let gs = distribute(vec![1], 2)
+            .execute(&taking_damage(true))
+            .unwrap();
+
+        assert_eq!(gs.crew[0].damage, 1);
+        assert_eq!(gs.ship_damage, 2);
+    }
+
+    #[test_case(distribute(vec![1, 1], 0), false; "Too little")]
+    #[test_case(distribute(vec![4], 0), false; "Too much")]
+    #[test_case(distribute(vec![1], 2), false; "Ship not allowed")]
+    #[test_case(distribute(vec![0; 1000], 3), true; "No such crew")]
+    fn test_err_if_invalid(
+        action: DistributeDamageAction,
+        ship: bool,
+    ) {
+        assert!(action.execute(&taking_damage(ship)).is_err());
+    }
+
+    #[test]
+    fn test_err_if_knocked_out() {
+        let mut gs = taking_damage(false);
+        gs.crew[0].damage = gs.crew[0].max_health;
+
+        assert!(distribute(vec![3], 0).execute(&gs).is_err());
+    }
+
+    #[test]
+    fn test_game_over_when_captain_down() {
+        let mut gs = taking_damage(false);
+        let captain = gs.crew.iter().position(|c| c.captain).unwrap();
+        gs.crew[captain].damage = gs.crew[captain].max_health - 1;
+        let mut damage = vec![0; captain + 1];
+        damage[captain] = 3;
+
+        let gs = distribute(damage, 0).execute(&gs).unwrap();
+        assert_eq!(
+            gs.crew[captain].damage,
+            gs.crew[captain].max_health
+        );
+        assert!(gs.is_game_over());
+    }
+
+    #[test]
+    fn test_game_over_when_crew_down() {
+        let mut gs = taking_damage(false);
+        for crew in gs.crew.iter_mut().skip(1) {
+            crew.damage = crew.max_health;
+        }
+        gs.crew[0].damage = gs.crew[0].max_health - 3;
+
+        let gs = distribute(vec![3], 0).execute(&gs).unwrap();
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::GameOver { reason } if reason == "game_over.crew_down"
+        ));
+    }
+
+    #[test]
+    fn test_err_outside_damage_phase() {
+        let gs = GameState::init_state();
+
+        assert!(distribute(vec![3], 0).execute(&gs).is_err());
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::game_state::{
-    game_phase::ShipActionSubphase, GamePhase, GameState, Update,
+    client_message::ClientMessage, game_phase::ShipActionSubphase,
+    GamePhase, GameState, Update,
 };
 
 use super::Action;
@@ -23,25 +24,33 @@ impl Action for DrawForDeckAction {
                 },
             )) => {
                 if search_tokens_drawn.len() < 3 {
-                    match gs.search_token_deck.draw() {
+                    match gs.search_token_deck.draw(&mut gs.rng) {
                         Ok(token) => {
                             let mut search_tokens_drawn =
                                 search_tokens_drawn.clone();
-                            search_tokens_drawn.push(token);
+                            search_tokens_drawn.push(token.clone());
                             let phase = GamePhase::ShipActionPhase(Some(
                                 ShipActionSubphase::DeckAction {
                                     search_tokens_drawn,
                                 },
                             ));
-                            Ok(gs).and_then(|g| g.set_phase(phase))
+                            Ok(gs)
+                                .and_then(|g| g.set_phase(phase))
+                                .and_then(|g| {
+                                    g.log_event(
+                                        ClientMessage::DrewSearchToken {
+                                            token,
+                                        },
+                                    )
+                                })
                         }
                         Err(err) => Err(err),
                     }
                 } else {
-                    Err("You may only draw 3 tokens".to_owned())
+                    Err("error.token_limit".to_owned())
                 }
             }
-            _ => Err("Wrong phase.".to_owned()),
+            _ => Err("error.wrong_phase".to_owned()),
         }
     }
 }
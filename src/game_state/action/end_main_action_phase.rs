@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{GamePhase, GameState, Update};
+
+// The players don't have to take every main action they're allowed
+#[derive(Deserialize, Serialize)]
+pub struct EndMainActionPhase {
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "endMainActionPhase")]
+impl Action for EndMainActionPhase {
+    fn execute(&self, state: &GameState) -> Update {
+        match state.phase() {
+            GamePhase::MainActionPhase { current: None, .. } => {
+                state.set_phase(GamePhase::EndTurnPhase)
+            }
+            GamePhase::MainActionPhase { .. } => {
+                Err("error.finish_main_action".to_owned())
+            }
+            _ => Err("error.wrong_phase".to_owned()),
+        }
+    }
+}
+
+impl Display for EndMainActionPhase {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "End Main Action Phase")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::game_phase::MainActionSubphase;
+
+    #[test]
+    fn test_err_if_action_unfinished() {
+        let action = EndMainActionPhase { player_ix: 0 };
+        let gs = GameState::init_state()
+            .set_phase(GamePhase::MainActionPhase {
+                taken: 1,
+                current: Some(MainActionSubphase::Port),
+            })
+            .unwrap();
+        assert!(action.execute(&gs).is_err());
+
+        let gs = gs
+            .set_phase(GamePhase::MainActionPhase {
+                taken: 1,
+                current: None,
+            })
+            .unwrap();
+        assert!(matches!(
+            action.execute(&gs).unwrap().phase(),
+            GamePhase::EndTurnPhase
+        ));
+    }
+}
@@ -1,33 +1,91 @@
-// use std::fmt::Display;
-//
-// use serde::Deserialize;
-//
-// use crate::game_state::{GamePhase, GameState, Update};
-//
-// use super::Action;
-//
-// #[derive(Deserialize)]
-// pub struct EndTurnAction {
-//     player_ix: usize,
-// }
-//
-// impl Action for EndTurnAction {
-//     fn execute(&self, state: &GameState) -> Update {
-//         if let GamePhase::EventPhase(Some(_)) = state.phase() {
-//             let mut gs = state.clone();
-//             gs.set_phase(GamePhase::ShipAction(None));
-//             Ok(gs)
-//         } else {
-//             Err("You can't end the turn yet".to_owned())
-//         }
-//     }
-// }
-//
-// impl Display for EndTurnAction {
-//     fn fmt(
-//         &self,
-//         f: &mut std::fmt::Formatter<'_>,
-//     ) -> std::fmt::Result {
-//         write!(f, "End Turn Action")
-//     }
-// }
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{GamePhase, GameState, Update};
+
+#[derive(Deserialize, Serialize)]
+pub struct EndTurnAction {
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "endTurnAction")]
+impl Action for EndTurnAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::EndTurnPhase = state.phase() {
+            state.clone().end_turn().and_then(|g| {
+                g.set_phase(GamePhase::ShipActionPhase(None))
+            })
+        } else {
+            Err("error.wrong_phase".to_owned())
+        }
+    }
+}
+
+impl Display for EndTurnAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "End Turn Action")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{player::Player, ShipRoom};
+
+    fn end_turn(gs: &GameState) -> GameState {
+        EndTurnAction { player_ix: 0 }.execute(gs).unwrap()
+    }
+
+    #[test]
+    fn test_end_turn() {
+        let mut gs = GameState::init_state()
+            .set_phase(GamePhase::EndTurnPhase)
+            .unwrap();
+        gs.players.push(Player::default());
+        gs.room = ShipRoom::Galley;
+
+        let gs = end_turn(&gs);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ShipActionPhase(None)
+        ));
+        assert_eq!(gs.room, ShipRoom::None);
+        assert_eq!(gs.previous_room, ShipRoom::Galley);
+        assert_eq!((gs.turn, gs.round, gs.active_player), (2, 1, 1));
+
+        let gs =
+            end_turn(&gs.set_phase(GamePhase::EndTurnPhase).unwrap());
+        assert_eq!(gs.previous_room, ShipRoom::None);
+        assert_eq!((gs.turn, gs.round, gs.active_player), (3, 2, 0));
+    }
+
+    #[test]
+    fn test_crew_eats_each_round() {
+        let mut gs = GameState::init_state()
+            .set_phase(GamePhase::EndTurnPhase)
+            .unwrap();
+        gs.resources.meat = 1;
+
+        let gs = end_turn(&gs);
+        assert_eq!(gs.resources.meat, 0);
+        assert!(gs.crew.iter().all(|c| c.fatigue == 0));
+
+        let gs =
+            end_turn(&gs.set_phase(GamePhase::EndTurnPhase).unwrap());
+        assert!(gs.crew.iter().all(|c| c.fatigue == 1));
+    }
+
+    #[test]
+    fn test_err_if_main_actions_not_done() {
+        let gs = GameState::init_state()
+            .set_phase(GamePhase::main_actions())
+            .unwrap();
+
+        assert!(EndTurnAction { player_ix: 0 }.execute(&gs).is_err());
+    }
+}
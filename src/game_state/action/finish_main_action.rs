@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{GamePhase, GameState, Update};
+
+// Finishes the main action being taken, e.g. leaving the port
+#[derive(Deserialize, Serialize)]
+pub struct FinishMainAction {
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "finishMainAction")]
+impl Action for FinishMainAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::MainActionPhase {
+            taken,
+            current: Some(_),
+        } = state.phase()
+        {
+            state.set_phase(GamePhase::MainActionPhase {
+                taken,
+                current: None,
+            })
+        } else {
+            Err("error.wrong_phase".to_owned())
+        }
+    }
+}
+
+impl Display for FinishMainAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "Finish Main Action")
+    }
+}
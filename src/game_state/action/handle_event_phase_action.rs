@@ -3,6 +3,7 @@ use std::fmt;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::game_state::client_message::ClientMessage;
 use crate::game_state::GamePhase;
 use crate::game_state::Update;
 
@@ -20,8 +21,13 @@ impl Action for HandleEventPhaseAction {
         if let GamePhase::EventPhase(None) = state.phase() {
             let mut gs = state.clone();
 
-            match gs.event_card_deck.draw() {
+            match gs.event_card_deck.draw(&mut gs.rng) {
                 Ok(event_card) => Ok(gs)
+                    .and_then(|g| {
+                        g.log_event(ClientMessage::DrewEventCard {
+                            name: event_card.name.clone(),
+                        })
+                    })
                     .and_then(|g| {
                         g.set_phase(GamePhase::EventPhase(Some(
                             event_card,
@@ -30,7 +36,7 @@ impl Action for HandleEventPhaseAction {
                 Err(e) => Err(e),
             }
         } else {
-            Err("Wrong phase".to_owned())
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
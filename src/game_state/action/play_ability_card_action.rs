@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    client_message::ClientMessage,
+    effect::{apply_effects, EffectContext},
+    locale::message,
+    GameState, Update,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct PlayAbilityCardAction {
+    player_ix: usize,
+    card_ix: usize,
+    #[serde(default)]
+    crew_ix: Option<usize>,
+}
+
+#[typetag::serde(name = "playAbilityCardAction")]
+impl Action for PlayAbilityCardAction {
+    fn execute(&self, state: &GameState) -> Update {
+        let card = state
+            .players
+            .get(self.player_ix)
+            .and_then(|p| p.hand.get(self.card_ix))
+            .ok_or("error.no_card")?
+            .clone();
+
+        if !card.can_play(&state.phase()) {
+            return Err(message(
+                "error.cant_play_now",
+                &[&card.name],
+            ));
+        }
+
+        let ctx = EffectContext {
+            player_ix: self.player_ix,
+            crew_ix: self.crew_ix,
+            ..EffectContext::default()
+        };
+        state
+            .clone()
+            .discard_card(self.player_ix, self.card_ix)
+            .and_then(|g| apply_effects(&card.effects, &g, &ctx))
+            .and_then(|g| {
+                g.log_event(ClientMessage::PlayedAbilityCard { card })
+            })
+    }
+}
+
+impl Display for PlayAbilityCardAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Play Ability Card\n{}\n{}",
+            self.player_ix, self.card_ix
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{challenge::Challenge, GamePhase};
+
+    fn with_card(state: GameState, name: &str) -> GameState {
+        let mut gs = state;
+        let card = gs.ability_deck.take(|c| c.name == name).unwrap();
+        gs.players[0].add_card(card);
+        gs
+    }
+
+    #[test]
+    fn test_heal_and_discard() {
+        let mut gs =
+            with_card(GameState::init_state(), "ability.triage");
+        gs.crew[0].damage = 3;
+        let action = PlayAbilityCardAction {
+            player_ix: 0,
+            card_ix: 0,
+            crew_ix: Some(0),
+        };
+
+        let gs = action.execute(&gs).unwrap();
+        assert_eq!(gs.crew[0].damage, 1);
+        assert!(gs.players[0].hand.is_empty());
+    }
+
+    #[test]
+    fn test_err_if_played_outside_challenge() {
+        let gs = with_card(
+            GameState::init_state(),
+            "ability.focused_mind",
+        );
+        let action = PlayAbilityCardAction {
+            player_ix: 0,
+            card_ix: 0,
+            crew_ix: None,
+        };
+
+        assert!(action.execute(&gs).is_err());
+    }
+
+    #[test]
+    fn test_boost_challenge() {
+        let gs = GameState::init_state()
+            .challenge(Challenge::default())
+            .unwrap();
+        let gs = with_card(gs, "ability.focused_mind");
+        let action = PlayAbilityCardAction {
+            player_ix: 0,
+            card_ix: 0,
+            crew_ix: None,
+        };
+
+        let gs = action.execute(&gs).unwrap();
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ChallengePhase { boost: 2, .. }
+        ));
+    }
+}
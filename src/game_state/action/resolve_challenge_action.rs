@@ -4,65 +4,82 @@ use serde::{Deserialize, Serialize};
 
 use super::Action;
 use crate::game_state::{
-    challenge::Challenge, GamePhase, GameState, Update,
+    challenge::ChallengeResult, client_message::ClientMessage,
+    effect::{apply_effects, EffectContext},
+    GamePhase, GameState, Update,
 };
 
+// Confirms the crew and boost committed to the challenge
 #[derive(Deserialize, Serialize)]
 pub struct ResolveChallengeAction {
-    selected_crew: Vec<usize>,
-}
-
-impl ResolveChallengeAction {
-    fn crew_skill(
-        &self,
-        state: &GameState,
-        challenge: &Challenge,
-    ) -> u32 {
-        let mut total = 0;
-        for crew_ix in self.selected_crew.iter() {
-            let crew = &state.crew[*crew_ix];
-            total += crew.skills[&challenge.skill];
-        }
-        total
-    }
+    player_ix: usize,
 }
 
 #[typetag::serde(name="resolveChallengeAction")]
 impl Action for ResolveChallengeAction {
     fn execute(&self, state: &GameState) -> Update {
-        let gs = state.clone();
-
         if let GamePhase::ChallengePhase {
             challenge,
-            added: None,
+            result: None,
+            boost,
+            crew,
+            ..
         } = state.phase()
         {
-            let added = 4;
-            let phase = GamePhase::ChallengePhase {
-                challenge: challenge.clone(),
-                added: Some(added),
+            // Worked out again as the crew may have been hurt or
+            // tired since they were committed
+            let crew_skill = state.crew_skill(&crew, challenge.skill);
+            let total = crew_skill
+                .checked_add(boost)
+                .ok_or("error.boost_too_large")?;
+
+            // The fate card is what makes the outcome uncertain
+            let mut gs = state.clone();
+            let fate_card = gs.fate_deck.draw(&mut gs.rng)?;
+            gs.fate_deck.add_to_discard(&fate_card);
+
+            let result = ChallengeResult {
+                crew: crew_skill,
+                boost,
+                total: total + fate_card.value,
+                passed: total + fate_card.value >= challenge.amount,
+                fate_card,
             };
+            let gs = gs.log_event(ClientMessage::ChallengeResolved {
+                skill: challenge.skill,
+                total: result.total,
+                target: challenge.amount,
+            })?;
 
-            if self.crew_skill(state, &challenge) + added
-                >= challenge.amount
-            {
-                (challenge.if_succeed)(&gs)
+            let effects = if result.passed {
+                challenge.success.clone()
             } else {
-                (challenge.if_fail)(&gs)
-            }
-
-            .map(|g| {
-                let mut gs = g.clone();
-                for crew_ix in self.selected_crew.iter() {
-                    gs.crew[*crew_ix].change_fatigue(1)
-                }
-                gs
+                challenge.failure.clone()
+            };
+
+            // The result goes on first so a phase opened by the
+            // effects, like sharing out damage, is dealt with before
+            // the result is accepted
+            gs.set_phase(GamePhase::ChallengePhase {
+                challenge,
+                result: Some(result),
+                boost,
+                crew: crew.clone(),
+                total,
+            })
+
+            .and_then(|g| {
+                crew.iter()
+                    .try_fold(g, |g, ix| g.change_fatigue(*ix, 1))
             })
 
-            .and_then(|g| g.set_phase(phase))
+            .and_then(|g| {
+                let ctx = EffectContext::challenge(crew);
+                apply_effects(&effects, &g, &ctx)
+            })
 
         } else {
-            Err("Wrong phase".to_owned())
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
@@ -72,6 +89,94 @@ impl Display for ResolveChallengeAction {
         &self,
         f: &mut std::fmt::Formatter<'_>,
     ) -> std::fmt::Result {
-        write!(f, "Resolve Challenge Action")
+        write!(f, "Resolve Challenge Action\n{}", self.player_ix)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{
+        challenge::Challenge,
+        content::{Content, DEFAULT_CONTENT_DIR},
+        skill::Skill,
+    };
+
+    fn resolve(seed: u64) -> ChallengeResult {
+        let content = Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        let challenge = Challenge {
+            skill: Skill::Savvy,
+            amount: 5,
+            ..Challenge::default()
+        };
+        let gs = GameState::new(&content, seed)
+            .challenge(challenge)
+            .and_then(|g| {
+                g.update_challenge(|crew, _| {
+                    *crew = vec![2, 4];
+                    Ok(())
+                })
+            })
+            .unwrap();
+        let action = ResolveChallengeAction { player_ix: 0 };
+
+        match action.execute(&gs).unwrap().phase() {
+            GamePhase::ChallengePhase {
+                result: Some(result),
+                ..
+            } => result,
+            _ => panic!("The challenge wasn't resolved"),
+        }
+    }
+
+    #[test]
+    fn test_fate_card_is_added() {
+        let result = resolve(0);
+
+        assert_eq!(result.crew, 2);
+        assert_eq!(result.total, 2 + result.fate_card.value);
+        assert_eq!(result.passed, result.total >= 5);
+    }
+
+    #[test]
+    fn test_total_follows_crew_changes() {
+        let content = Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        let challenge = Challenge {
+            skill: Skill::Savvy,
+            amount: 5,
+            ..Challenge::default()
+        };
+        let gs = GameState::new(&content, 0)
+            .challenge(challenge)
+            .and_then(|g| {
+                g.update_challenge(|crew, _| {
+                    *crew = vec![2];
+                    Ok(())
+                })
+            })
+            .unwrap();
+        assert_eq!(gs.crew_skill(&[2], Skill::Savvy), 1);
+
+        // Tired after being committed, so they add nothing
+        let gs = gs.change_fatigue(2, 1).unwrap();
+        let action = ResolveChallengeAction { player_ix: 0 };
+
+        match action.execute(&gs).unwrap().phase() {
+            GamePhase::ChallengePhase {
+                result: Some(result),
+                ..
+            } => assert_eq!(result.crew, 0),
+            _ => panic!("The challenge wasn't resolved"),
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_result() {
+        assert_eq!(resolve(7), resolve(7));
+
+        let values: Vec<u32> = (0..20)
+            .map(|seed| resolve(seed).fate_card.value)
+            .collect();
+        assert!(values.iter().any(|v| *v != values[0]));
     }
 }
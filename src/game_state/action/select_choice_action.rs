@@ -0,0 +1,106 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    effect::{apply_effects, EffectContext},
+    GamePhase, GameState, Update,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct SelectChoiceAction {
+    option_ix: usize,
+    player_ix: usize,
+    #[serde(default)]
+    crew_ix: Option<usize>,
+}
+
+#[typetag::serde(name = "selectChoiceAction")]
+impl Action for SelectChoiceAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::ChoicePhase(options) = state.phase() {
+            let option = options
+                .get(self.option_ix)
+                .ok_or("error.bad_option".to_owned())?;
+
+            let ctx = EffectContext {
+                player_ix: self.player_ix,
+                crew_ix: self.crew_ix,
+                ..EffectContext::default()
+            };
+            state.pop_phase().and_then(|g| {
+                apply_effects(&option.effects, &g, &ctx)
+            })
+        } else {
+            Err("error.wrong_phase".to_owned())
+        }
+    }
+}
+
+impl Display for SelectChoiceAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "Select Choice")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{
+        effect::{ChoiceOption, Effect},
+        Resource,
+    };
+
+    fn choice_state() -> GameState {
+        let options = vec![
+            ChoiceOption {
+                text: "Coins".to_owned(),
+                effects: vec![Effect::GainResource {
+                    resource: Resource::Coins,
+                    amount: 2,
+                }],
+            },
+            ChoiceOption {
+                text: "Meat".to_owned(),
+                effects: vec![Effect::GainResource {
+                    resource: Resource::Meat,
+                    amount: 1,
+                }],
+            },
+        ];
+        GameState::init_state()
+            .push_phase(GamePhase::ChoicePhase(options))
+    }
+
+    #[test]
+    fn test_select_choice() {
+        let action = SelectChoiceAction {
+            option_ix: 1,
+            player_ix: 0,
+            crew_ix: None,
+        };
+
+        let gs = action.execute(&choice_state()).unwrap();
+        assert_eq!(gs.resources.meat, 1);
+        assert_eq!(gs.resources.coins, 0);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ShipActionPhase(None)
+        ));
+    }
+
+    #[test]
+    fn test_err_if_option_out_of_range() {
+        let action = SelectChoiceAction {
+            option_ix: 2,
+            player_ix: 0,
+            crew_ix: None,
+        };
+
+        assert!(action.execute(&choice_state()).is_err());
+    }
+}
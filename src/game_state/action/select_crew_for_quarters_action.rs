@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    game_phase::ShipActionSubphase, locale::message, GamePhase,
+    GameState, Update,
+};
+
+// How many crew members can rest in the quarters
+const QUARTERS_BEDS: usize = 2;
+
+#[derive(Deserialize, Serialize)]
+pub struct SelectCrewForQuartersAction {
+    // Each loses 1 fatigue, none to decline
+    crew: Vec<usize>,
+    player_ix: usize,
+}
+
+impl SelectCrewForQuartersAction {
+    fn check_crew(&self) -> Result<(), String> {
+        if self.crew.len() > QUARTERS_BEDS {
+            return Err(message(
+                "error.too_many_crew",
+                &[&QUARTERS_BEDS],
+            ));
+        }
+        for (ix, crew_ix) in self.crew.iter().enumerate() {
+            if self.crew[..ix].contains(crew_ix) {
+                return Err("error.crew_chosen_twice".to_owned());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[typetag::serde(name = "selectCrewForQuartersAction")]
+impl Action for SelectCrewForQuartersAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::ShipActionPhase(Some(
+            ShipActionSubphase::QuartersAction,
+        )) = state.phase()
+        {
+            self.check_crew()?;
+
+            self.crew
+                .iter()
+                .try_fold(state.clone(), |g, ix| {
+                    g.change_fatigue(*ix, -1)
+                })
+                .and_then(|g| {
+                    g.set_phase(GamePhase::EventPhase(None))
+                })
+        } else {
+            Err("error.wrong_phase".to_owned())
+        }
+    }
+}
+
+impl Display for SelectCrewForQuartersAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "Select Crew For Quarters: {:?}", self.crew)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    fn in_quarters() -> GameState {
+        GameState::init_state()
+            .set_phase(GamePhase::ShipActionPhase(Some(
+                ShipActionSubphase::QuartersAction,
+            )))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rest_crew() {
+        let mut gs = in_quarters();
+        gs.crew[1].fatigue = 2;
+        gs.crew[5].fatigue = 1;
+        let action = SelectCrewForQuartersAction {
+            crew: vec![1, 5],
+            player_ix: 0,
+        };
+
+        let gs = action.execute(&gs).unwrap();
+        assert_eq!(gs.crew[1].fatigue, 1);
+        assert_eq!(gs.crew[5].fatigue, 0);
+        assert!(matches!(gs.phase(), GamePhase::EventPhase(None)));
+    }
+
+    #[test_case(vec![0, 1, 2]; "too many")]
+    #[test_case(vec![3, 3]; "same crew twice")]
+    #[test_case(vec![99]; "no such crew")]
+    fn test_err_if_bad_crew(crew: Vec<usize>) {
+        let action =
+            SelectCrewForQuartersAction { crew, player_ix: 0 };
+
+        assert!(action.execute(&in_quarters()).is_err());
+    }
+}
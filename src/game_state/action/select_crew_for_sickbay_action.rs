@@ -0,0 +1,85 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    game_phase::ShipActionSubphase, GamePhase, GameState, Update,
+};
+
+// Damage healed on the crew member treated in the sickbay
+const SICKBAY_HEALING: u8 = 2;
+
+#[derive(Deserialize, Serialize)]
+pub struct SelectCrewForSickbayAction {
+    // None to decline
+    #[serde(default)]
+    crew_ix: Option<usize>,
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "selectCrewForSickbayAction")]
+impl Action for SelectCrewForSickbayAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::ShipActionPhase(Some(
+            ShipActionSubphase::SickbayAction,
+        )) = state.phase()
+        {
+            let gs = match self.crew_ix {
+                Some(crew_ix) => {
+                    state.clone().heal(crew_ix, SICKBAY_HEALING)
+                }
+                None => Ok(state.clone()),
+            };
+            gs.and_then(|g| g.set_phase(GamePhase::EventPhase(None)))
+        } else {
+            Err("error.wrong_phase".to_owned())
+        }
+    }
+}
+
+impl Display for SelectCrewForSickbayAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "Select Crew For Sickbay: {:?}", self.crew_ix)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn in_sickbay() -> GameState {
+        GameState::init_state()
+            .set_phase(GamePhase::ShipActionPhase(Some(
+                ShipActionSubphase::SickbayAction,
+            )))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_heal_crew() {
+        let mut gs = in_sickbay();
+        gs.crew[2].damage = 3;
+        let action = SelectCrewForSickbayAction {
+            crew_ix: Some(2),
+            player_ix: 0,
+        };
+
+        let gs = action.execute(&gs).unwrap();
+        assert_eq!(gs.crew[2].damage, 1);
+        assert!(matches!(gs.phase(), GamePhase::EventPhase(None)));
+    }
+
+    #[test]
+    fn test_err_if_no_crew() {
+        let action = SelectCrewForSickbayAction {
+            crew_ix: Some(99),
+            player_ix: 0,
+        };
+
+        assert!(action.execute(&in_sickbay()).is_err());
+    }
+}
@@ -22,7 +22,7 @@ fn validate(state: &GameState) -> Update {
     {
         Ok(state.clone())
     } else {
-        Err("Wrong phase".to_owned())
+        Err("error.wrong_phase".to_owned())
     }
 }
 
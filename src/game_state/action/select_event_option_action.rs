@@ -1,13 +1,18 @@
 use serde::{Deserialize, Serialize};
 
 use super::Action;
-use crate::game_state::{GamePhase, GameState, Update};
+use crate::game_state::{
+    client_message::ClientMessage, effect::EffectContext, GamePhase,
+    GameState, Update,
+};
 
 #[derive(Deserialize, Serialize)]
 pub struct SelectEventOptionAction {
     option_ix: usize,
-    #[allow(dead_code)]
     player_ix: usize,
+    // For options that affect a single crew member
+    #[serde(default)]
+    crew_ix: Option<usize>,
 }
 
 #[typetag::serde(name="selectEventOptionAction")]
@@ -22,14 +27,26 @@ impl Action for SelectEventOptionAction {
                     gs.event_card_deck.add_to_discard(card);
                     Ok(gs)
                         .and_then(|g| {
-                            g.set_phase(GamePhase::MainActionPhase(Vec::new()))
+                            g.log_event(ClientMessage::ChoseEventOption {
+                                text: option.text.clone(),
+                            })
+                        })
+                        .and_then(|g| {
+                            g.set_phase(GamePhase::main_actions())
+                        })
+                        .and_then(|g| {
+                            let ctx = EffectContext {
+                                player_ix: self.player_ix,
+                                crew_ix: self.crew_ix,
+                                ..EffectContext::default()
+                            };
+                            option.choose(&g, &ctx)
                         })
-                        .and_then(|g| (option.handle_option)(&g))
                 }
-                None => Err("option index not valid".to_owned()),
+                None => Err("error.bad_option".to_owned()),
             }
         } else {
-            Err("wrong phase".to_owned())
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
@@ -2,29 +2,134 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use crate::game_state::{game_phase::{GamePhase, MainActionSubphase}, GameState, Update};
+use crate::game_state::{
+    client_message::ClientMessage,
+    game_phase::{GamePhase, MainActionSubphase},
+    locale::message,
+    map::Terrain,
+    GameState, Update,
+};
 
 use super::Action;
 
+// How many main actions can be taken each turn
+pub const MAIN_ACTION_LIMIT: u32 = 2;
+
+// Command tokens spent to explore
+const EXPLORE_COST: u32 = 1;
+
 #[derive(Serialize, Deserialize)]
 pub struct SelectMainAction {
-    //TODO action type
-    player_ix: u32,
+    action: MainActionSubphase,
+    player_ix: usize,
+    // Which of the area's ports to dock at
+    #[serde(default)]
+    port_ix: usize,
+}
+
+impl SelectMainAction {
+    fn check_allowed(&self, state: &GameState) -> Result<(), String> {
+        let area = state.map.current_area();
+        match self.action {
+            MainActionSubphase::Port
+                if area.ports.get(self.port_ix).is_none() =>
+            {
+                Err("error.no_port".to_owned())
+            }
+            MainActionSubphase::Explore
+                if area.terrain != Terrain::Coast =>
+            {
+                Err("error.not_coast".to_owned())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn pay_cost(&self, state: GameState) -> Update {
+        match self.action {
+            MainActionSubphase::Explore => state
+                .spend_command_tokens(self.player_ix, EXPLORE_COST),
+            _ => Ok(state),
+        }
+    }
+
+    // Travel, the port and exploring go on until they're finished,
+    // resting happens at once
+    fn start(&self, state: GameState, taken: u32) -> Update {
+        match self.action {
+            MainActionSubphase::Rest => (0..state.crew.len())
+                .filter(|ix| state.crew[*ix].fatigue > 0)
+                .try_fold(state.clone(), |g, ix| {
+                    g.change_fatigue(ix, -1)
+                })
+                .and_then(|g| {
+                    g.set_phase(GamePhase::MainActionPhase {
+                        taken,
+                        current: None,
+                    })
+                }),
+            MainActionSubphase::Explore => {
+                let area = state.map.current_area();
+                let msg = ClientMessage::Explored {
+                    area: state.map.ship_area,
+                    page: area.page,
+                };
+                state.queue_message(msg).and_then(|g| {
+                    g.set_phase(GamePhase::MainActionPhase {
+                        taken,
+                        current: Some(self.action),
+                    })
+                })
+            }
+            MainActionSubphase::Port => {
+                let port =
+                    &state.map.current_area().ports[self.port_ix];
+                let msg = ClientMessage::Docked {
+                    port: port.name.clone(),
+                    page: port.page,
+                };
+                state.queue_message(msg).and_then(|g| {
+                    g.set_phase(GamePhase::MainActionPhase {
+                        taken,
+                        current: Some(self.action),
+                    })
+                })
+            }
+            MainActionSubphase::Travel => {
+                state.set_phase(GamePhase::MainActionPhase {
+                    taken,
+                    current: Some(self.action),
+                })
+            }
+        }
+    }
 }
 
 #[typetag::serde(name = "selectMainAction")]
 impl Action for SelectMainAction {
     fn execute(&self, state: &GameState) -> Update {
-        if let GamePhase::MainActionPhase(actions) = state.phase() {
-            let mut actions = actions.clone();
-            actions.push(MainActionSubphase::Travel);
+        if let GamePhase::MainActionPhase {
+            taken,
+            current: None,
+        } = state.phase()
+        {
+            if taken >= MAIN_ACTION_LIMIT {
+                return Err(message(
+                    "error.main_action_limit",
+                    &[&MAIN_ACTION_LIMIT],
+                ));
+            }
+            self.check_allowed(state)?;
 
-            let phase = GamePhase::MainActionPhase(actions);
-
-            Ok(state.clone()).and_then(|g| g.set_phase(phase))
-        }
-        else {
-            Err("wrong phase".to_owned())
+            self.pay_cost(state.clone())
+                .and_then(|g| {
+                    g.log_event(ClientMessage::TookMainAction {
+                        action: self.action,
+                    })
+                })
+                .and_then(|g| self.start(g, taken + 1))
+        } else {
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
@@ -34,6 +139,89 @@ impl Display for SelectMainAction {
         &self,
         f: &mut std::fmt::Formatter<'_>,
     ) -> std::fmt::Result {
-        write!(f, "Select Main Action")
+        write!(f, "Select Main Action: {:?}", self.action)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    fn select(gs: &GameState, action: MainActionSubphase) -> Update {
+        SelectMainAction {
+            action,
+            player_ix: 0,
+            port_ix: 0,
+        }
+        .execute(gs)
+    }
+
+    fn main_phase(taken: u32) -> GameState {
+        GameState::init_state()
+            .set_phase(GamePhase::MainActionPhase {
+                taken,
+                current: None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_explore_costs_a_command_token() {
+        let mut gs = main_phase(0);
+        assert!(select(&gs, MainActionSubphase::Explore).is_err());
+
+        gs.players[0].command_tokens = 2;
+        let gs = select(&gs, MainActionSubphase::Explore).unwrap();
+        assert_eq!(gs.players[0].command_tokens, 1);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::MainActionPhase {
+                taken: 1,
+                current: Some(MainActionSubphase::Explore)
+            }
+        ));
+    }
+
+    #[test]
+    fn test_rest() {
+        let mut gs = main_phase(0);
+        gs.crew[3].fatigue = 2;
+
+        let gs = select(&gs, MainActionSubphase::Rest).unwrap();
+        assert_eq!(gs.crew[3].fatigue, 1);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::MainActionPhase {
+                taken: 1,
+                current: None
+            }
+        ));
+    }
+
+    #[test]
+    fn test_dock_at_port() {
+        let gs =
+            select(&main_phase(0), MainActionSubphase::Port).unwrap();
+
+        assert!(gs.message_queue.iter().any(|m| matches!(
+            m,
+            ClientMessage::Docked { page: 101, .. }
+        )));
+    }
+
+    #[test]
+    fn test_err_if_no_port() {
+        let gs = main_phase(0).move_ship(2).unwrap();
+
+        assert!(select(&gs, MainActionSubphase::Port).is_err());
+    }
+
+    #[test_case(MainActionSubphase::Travel)]
+    #[test_case(MainActionSubphase::Rest)]
+    fn test_err_if_over_limit(action: MainActionSubphase) {
+        assert!(
+            select(&main_phase(MAIN_ACTION_LIMIT), action).is_err()
+        );
     }
 }
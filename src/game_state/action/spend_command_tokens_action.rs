@@ -0,0 +1,100 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{GameState, Update};
+
+// Each command token spent adds one to the challenge total
+#[derive(Deserialize, Serialize)]
+pub struct SpendCommandTokensAction {
+    amount: u32,
+    player_ix: usize,
+}
+
+#[typetag::serde(name = "spendCommandTokensAction")]
+impl Action for SpendCommandTokensAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if self.amount == 0 {
+            return Err("error.no_tokens_spent".to_owned());
+        }
+
+        // Spending first checks the amount against what the player
+        // holds
+        state
+            .clone()
+            .spend_command_tokens(self.player_ix, self.amount)
+            .and_then(|g| g.boost_challenge(self.amount))
+    }
+}
+
+impl Display for SpendCommandTokensAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Spend Command Tokens Action\n{}\n{}",
+            self.amount, self.player_ix
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{challenge::Challenge, GamePhase};
+
+    #[test]
+    fn test_tokens_boost_challenge() {
+        let mut gs = GameState::init_state()
+            .challenge(Challenge::default())
+            .unwrap();
+        gs.players[0].command_tokens = 3;
+        let action = SpendCommandTokensAction {
+            amount: 2,
+            player_ix: 0,
+        };
+
+        let gs = action.execute(&gs).unwrap();
+        assert_eq!(gs.players[0].command_tokens, 1);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::ChallengePhase {
+                boost: 2,
+                total: 2,
+                ..
+            }
+        ));
+        assert!(action.execute(&gs).is_err());
+    }
+
+    #[test]
+    fn test_err_if_amount_not_held() {
+        let mut gs = GameState::init_state()
+            .challenge(Challenge::default())
+            .unwrap();
+        gs.players[0].command_tokens = 3;
+
+        for amount in [0, u32::MAX] {
+            let action = SpendCommandTokensAction {
+                amount,
+                player_ix: 0,
+            };
+            assert!(action.execute(&gs).is_err());
+        }
+    }
+
+    #[test]
+    fn test_err_outside_challenge() {
+        let mut gs = GameState::init_state();
+        gs.players[0].command_tokens = 3;
+        let action = SpendCommandTokensAction {
+            amount: 1,
+            player_ix: 0,
+        };
+
+        assert!(action.execute(&gs).is_err());
+    }
+}
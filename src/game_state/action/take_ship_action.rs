@@ -5,8 +5,8 @@ use serde::{Deserialize, Serialize};
 use super::Action;
 
 use crate::game_state::{
-    game_phase::ShipActionSubphase, GamePhase, GameState, ShipRoom,
-    Update,
+    client_message::ClientMessage, game_phase::ShipActionSubphase,
+    GamePhase, GameState, ShipRoom, Update,
 };
 
 #[derive(Deserialize, Serialize)]
@@ -52,24 +52,54 @@ impl TakeShipAction {
             .and_then(|g| g.set_room(&ShipRoom::Galley))
             .and_then(|g| g.set_phase(phase))
     }
+
+    // The crew to rest or heal are chosen in the subphase
+    fn crew_room_action(
+        &self,
+        state: &GameState,
+        subphase: ShipActionSubphase,
+    ) -> Update {
+        let phase = GamePhase::ShipActionPhase(Some(subphase));
+
+        Ok(state.clone())
+            .and_then(|g| g.set_room(&self.room))
+            .and_then(|g| g.set_phase(phase))
+    }
 }
 
 #[typetag::serde(name="takeShipAction")]
 impl Action for TakeShipAction {
     fn execute(&self, state: &GameState) -> Update {
         if let GamePhase::ShipActionPhase(None) = &state.phase() {
-            if state.room == self.room {
-                Err("You cannot visit the same room twice".to_owned())
+            // Checked first, no room is taken on the first turn
+            if self.room == ShipRoom::None {
+                Err("error.no_room".to_owned())
+            } else if state.previous_room == self.room {
+                Err("error.same_room".to_owned())
             } else {
+                // Logged first so the transcript shows the room
+                // before what happened in it
+                let took_room = ClientMessage::TookRoom {
+                    room: self.room.clone(),
+                };
+                let gs = state.clone().log_event(took_room)?;
                 match self.room {
-                    ShipRoom::Bridge => self.bridge_action(state),
-                    ShipRoom::Galley => self.galley_action(state),
-                    ShipRoom::Deck => self.deck_action(state),
-                    _ => Err(String::from("Not implemented")),
+                    ShipRoom::Bridge => self.bridge_action(&gs),
+                    ShipRoom::Galley => self.galley_action(&gs),
+                    ShipRoom::Deck => self.deck_action(&gs),
+                    ShipRoom::Quarters => self.crew_room_action(
+                        &gs,
+                        ShipActionSubphase::QuartersAction,
+                    ),
+                    ShipRoom::Sickbay => self.crew_room_action(
+                        &gs,
+                        ShipActionSubphase::SickbayAction,
+                    ),
+                    ShipRoom::None => Err("error.no_room".to_owned()),
                 }
             }
         } else {
-            Err("Wrong phase.".to_owned())
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
@@ -117,7 +147,7 @@ mod test {
 
     #[test_case(Sa(Some(Sas::default())); "Current in ship action")]
     #[test_case(Ep(None); "In event phase")]
-    #[test_case(Cp {challenge: Challenge::default(), added: None}; "In challenge phase")]
+    #[test_case(Cp {challenge: Challenge::default(), result: None, boost: 0, crew: Vec::new(), total: 0}; "In challenge phase")]
     fn test_takeshipaction_err_if_wrong_phase(phase: GamePhase) {
         let gs = GameState::init_state().set_phase(phase).unwrap();
 
@@ -134,7 +164,10 @@ mod test {
     #[test_case(ShipRoom::Deck)]
     #[test_case(ShipRoom::Galley)]
     fn test_takeshipaction_err_if_same_room(room: ShipRoom) {
-        let gs = GameState::init_state().set_room(&room).unwrap();
+        let gs = GameState {
+            previous_room: room.clone(),
+            ..GameState::init_state()
+        };
 
         let action = TakeShipAction { room, player_ix: 0 };
 
@@ -142,6 +175,51 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test_case(ShipRoom::Quarters, Sas::QuartersAction)]
+    #[test_case(ShipRoom::Sickbay, Sas::SickbayAction)]
+    fn test_crew_room_action(room: ShipRoom, subphase: Sas) {
+        let action = TakeShipAction { room, player_ix: 0 };
+
+        let gs = action.execute(&GameState::init_state()).unwrap();
+        assert_eq!(
+            serde_json::to_value(gs.phase()).unwrap(),
+            serde_json::to_value(Sa(Some(subphase))).unwrap()
+        );
+    }
+
+    #[test_case(ShipRoom::None; "First turn")]
+    #[test_case(ShipRoom::Galley; "After the galley")]
+    fn test_err_if_no_room(previous_room: ShipRoom) {
+        let gs = GameState {
+            previous_room,
+            ..GameState::init_state()
+        };
+        let action = TakeShipAction {
+            room: ShipRoom::None,
+            player_ix: 0,
+        };
+
+        assert_eq!(
+            action.execute(&gs).err(),
+            Some("error.no_room".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_room_logged_first() {
+        let mut gs = TakeShipAction {
+            room: ShipRoom::Bridge,
+            player_ix: 0,
+        }
+        .execute(&GameState::init_state())
+        .unwrap();
+
+        assert!(matches!(
+            gs.take_events().first(),
+            Some(ClientMessage::TookRoom { room: ShipRoom::Bridge })
+        ));
+    }
+
     #[test]
     fn test_deck_action() {
         let gs = GameState::init_state();
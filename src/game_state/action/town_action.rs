@@ -0,0 +1,190 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+use crate::game_state::{
+    game_phase::MainActionSubphase, locale::message, GamePhase,
+    GameState, Resource, Update,
+};
+
+// Coins for each grain or meat
+const PROVISION_PRICE: u32 = 1;
+// Coins for each point of damage healed
+const HEALING_PRICE: u32 = 1;
+
+// What can be done while the ship is docked at a port
+#[derive(Deserialize, Serialize, Debug)]
+pub enum TownService {
+    BuyProvisions { resource: Resource, amount: u32 },
+    HealCrew { crew_ix: usize, amount: u8 },
+    BuyAdventureCard { market_ix: usize },
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TownAction {
+    service: TownService,
+    player_ix: usize,
+}
+
+impl TownAction {
+    fn buy_provisions(
+        &self,
+        state: &GameState,
+        resource: Resource,
+        amount: u32,
+    ) -> Update {
+        if resource == Resource::Coins {
+            return Err("error.not_provisions".to_owned());
+        }
+
+        state
+            .clone()
+            .pay(Resource::Coins, amount * PROVISION_PRICE)
+            .and_then(|g| g.gain_resource(resource, amount))
+    }
+
+    fn heal_crew(
+        &self,
+        state: &GameState,
+        crew_ix: usize,
+        amount: u8,
+    ) -> Update {
+        let crew = state
+            .crew
+            .get(crew_ix)
+            .ok_or("error.no_crew".to_owned())?;
+        // Only pay for the damage there is
+        let amount = amount.min(crew.damage);
+        if amount == 0 {
+            return Err(message(
+                "error.nothing_to_heal",
+                &[&crew.name],
+            ));
+        }
+
+        state
+            .clone()
+            .pay(Resource::Coins, u32::from(amount) * HEALING_PRICE)
+            .and_then(|g| g.heal(crew_ix, amount))
+    }
+}
+
+#[typetag::serde(name = "townAction")]
+impl Action for TownAction {
+    fn execute(&self, state: &GameState) -> Update {
+        if let GamePhase::MainActionPhase {
+            current: Some(MainActionSubphase::Port),
+            ..
+        } = state.phase()
+        {
+            match self.service {
+                TownService::BuyProvisions { resource, amount } => {
+                    self.buy_provisions(state, resource, amount)
+                }
+                TownService::HealCrew { crew_ix, amount } => {
+                    self.heal_crew(state, crew_ix, amount)
+                }
+                TownService::BuyAdventureCard { market_ix } => {
+                    state.clone().buy_adventure_card(market_ix)
+                }
+            }
+        } else {
+            Err("error.not_in_port".to_owned())
+        }
+    }
+}
+
+impl Display for TownAction {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "Town Action: {:?}", self.service)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn in_port(coins: u32) -> GameState {
+        let mut gs = GameState::init_state()
+            .set_phase(GamePhase::MainActionPhase {
+                taken: 1,
+                current: Some(MainActionSubphase::Port),
+            })
+            .unwrap();
+        gs.resources.coins = coins;
+        gs
+    }
+
+    fn town(gs: &GameState, service: TownService) -> Update {
+        TownAction {
+            service,
+            player_ix: 0,
+        }
+        .execute(gs)
+    }
+
+    #[test]
+    fn test_buy_provisions() {
+        let gs = town(
+            &in_port(3),
+            TownService::BuyProvisions {
+                resource: Resource::Meat,
+                amount: 2,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(gs.resources.coins, 1);
+        assert_eq!(gs.resources.meat, 2);
+    }
+
+    #[test]
+    fn test_heal_crew_pays_for_the_damage_healed() {
+        let mut gs = in_port(5);
+        gs.crew[1].damage = 2;
+
+        let gs = town(
+            &gs,
+            TownService::HealCrew {
+                crew_ix: 1,
+                amount: 4,
+            },
+        )
+        .unwrap();
+        assert_eq!(gs.crew[1].damage, 0);
+        assert_eq!(gs.resources.coins, 3);
+    }
+
+    #[test]
+    fn test_buy_adventure_card() {
+        let gs = in_port(10);
+        let card = gs.market[1].clone();
+
+        let gs =
+            town(&gs, TownService::BuyAdventureCard { market_ix: 1 })
+                .unwrap();
+        assert_eq!(gs.adventure_cards, vec![card.clone()]);
+        assert_eq!(gs.resources.coins, 10 - card.cost);
+        assert!(!gs.market.contains(&card));
+    }
+
+    #[test]
+    fn test_err_if_not_enough_coins() {
+        let service = TownService::BuyAdventureCard { market_ix: 0 };
+        assert!(town(&in_port(0), service).is_err());
+    }
+
+    #[test]
+    fn test_err_if_not_docked() {
+        let gs = GameState::init_state();
+        let service = TownService::BuyProvisions {
+            resource: Resource::Grain,
+            amount: 1,
+        };
+        assert!(town(&gs, service).is_err());
+    }
+}
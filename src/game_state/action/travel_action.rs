@@ -2,27 +2,77 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use crate::game_state::{game_phase::GamePhase, GameState, Update};
+use crate::game_state::{
+    game_phase::{GamePhase, MainActionSubphase},
+    locale::message,
+    GameState, Update,
+};
 
 use super::Action;
 
+// How many areas the ship can move in one travel action
+pub const TRAVEL_ALLOWANCE: usize = 3;
+
 #[derive(Deserialize, Serialize)]
 pub struct TravelAction {
-    to_area: u32,
+    // The areas to move through in order, ending where the ship stops
+    path: Vec<u32>,
     player_ix: u32,
 }
 
+impl TravelAction {
+    // Every step has to go to an area next to the one before
+    fn check_path(&self, state: &GameState) -> Result<(), String> {
+        if self.path.is_empty() {
+            return Err("error.no_path".to_owned());
+        }
+        if self.path.len() > TRAVEL_ALLOWANCE {
+            return Err(message(
+                "error.too_far",
+                &[&TRAVEL_ALLOWANCE],
+            ));
+        }
+
+        let mut from = state.map.ship_area;
+        for to in self.path.iter() {
+            if *to == from {
+                return Err("error.same_area".to_owned());
+            }
+            if !state.map.map_data.is_adjacent(from, *to) {
+                return Err(message(
+                    "error.not_adjacent",
+                    &[to, &from],
+                ));
+            }
+            from = *to;
+        }
+
+        Ok(())
+    }
+}
+
 #[typetag::serde(name = "travelAction")]
 impl Action for TravelAction {
     fn execute(&self, state: &GameState) -> Update {
-        if let GamePhase::MainActionPhase(_) = state.phase() {
-            if state.map.ship_area == self.to_area {
-                Err("You can't move to the same area".to_owned())
-            } else {
-                Ok(state.clone()).and_then(|g| g.move_ship(self.to_area))
-            }
+        if let GamePhase::MainActionPhase {
+            taken,
+            current: Some(MainActionSubphase::Travel),
+        } = state.phase()
+        {
+            self.check_path(state)?;
+
+            // Each step is a message so clients can show the route
+            self.path
+                .iter()
+                .try_fold(state.clone(), |g, to| g.move_ship(*to))
+                .and_then(|g| {
+                    g.set_phase(GamePhase::MainActionPhase {
+                        taken,
+                        current: None,
+                    })
+                })
         } else {
-            Err("wrong phase".to_owned())
+            Err("error.wrong_phase".to_owned())
         }
     }
 }
@@ -35,3 +85,58 @@ impl Display for TravelAction {
         write!(f, "Travel Action")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::client_message::ClientMessage;
+    use test_case::test_case;
+
+    fn travel(path: &[u32]) -> Update {
+        let gs = GameState::init_state()
+            .set_phase(GamePhase::MainActionPhase {
+                taken: 1,
+                current: Some(MainActionSubphase::Travel),
+            })
+            .unwrap();
+
+        TravelAction {
+            path: path.to_vec(),
+            player_ix: 0,
+        }
+        .execute(&gs)
+    }
+
+    #[test]
+    fn test_travel_along_path() {
+        let gs = travel(&[6, 8, 10]).unwrap();
+
+        assert_eq!(gs.map.ship_area, 10);
+        assert!(matches!(
+            gs.phase(),
+            GamePhase::MainActionPhase {
+                taken: 1,
+                current: None
+            }
+        ));
+        assert_eq!(
+            gs.message_queue
+                .iter()
+                .filter(|e| matches!(
+                    e,
+                    ClientMessage::ShipMoved { .. }
+                ))
+                .count(),
+            3
+        );
+    }
+
+    #[test_case(&[]; "no path")]
+    #[test_case(&[1]; "same area")]
+    #[test_case(&[3]; "not adjacent")]
+    #[test_case(&[6, 99]; "unknown area")]
+    #[test_case(&[2, 1, 2, 1]; "too far")]
+    fn test_err_if_bad_path(path: &[u32]) {
+        assert!(travel(path).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+// How many adventure cards are for sale at once
+pub const MARKET_SIZE: usize = 3;
+
+// Bought at the market and kept on the ship
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct AdventureCard {
+    pub name: String,
+    pub deck_ix: u32,
+    // Price in coins
+    pub cost: u32,
+}
+
+pub fn validate_adventure_cards(
+    cards: &[AdventureCard],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (ix, card) in cards.iter().enumerate() {
+        if cards[..ix].iter().any(|c| c.deck_ix == card.deck_ix) {
+            errors.push(format!(
+                "Adventure card {} reuses deck index {}",
+                card.name, card.deck_ix
+            ));
+        }
+    }
+
+    errors
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    adventure_deck::AdventureCard, content::Content, crew::Crew,
+    locale::message, map::GameMap, pack::PackRef, player::Player,
+    GamePhase, GameState, Resources, ShipRoom, Update,
+};
+
+// Everything that carries over from one session of the campaign
+// to the next
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    pub crew: Vec<Crew>,
+    pub resources: Resources,
+    pub players: Vec<Player>,
+    pub ship_area: u32,
+    pub packs: Vec<PackRef>,
+    pub adventure_cards: Vec<AdventureCard>,
+    pub ship_damage: u32,
+    pub turn: u32,
+    pub round: u32,
+    pub active_player: usize,
+    // Still can't be taken on the first turn after resuming
+    pub previous_room: ShipRoom,
+    // The session's seed, to replay it from the action log. Saves
+    // from before version 5 don't have it.
+    pub seed: Option<u64>,
+}
+
+impl Campaign {
+    // The content has to be loaded with these packs to resume
+    pub fn pack_ids(&self) -> Vec<String> {
+        self.packs.iter().map(|p| p.id.clone()).collect()
+    }
+}
+
+impl GameState {
+    pub fn end_session(&self) -> Result<Campaign, String> {
+        if let GamePhase::ShipActionPhase(None) = self.phase() {
+            Ok(Campaign {
+                crew: self.crew.clone(),
+                resources: self.resources.clone(),
+                players: self.players.clone(),
+                ship_area: self.map.ship_area,
+                packs: self.packs.clone(),
+                adventure_cards: self.adventure_cards.clone(),
+                ship_damage: self.ship_damage,
+                turn: self.turn,
+                round: self.round,
+                active_player: self.active_player,
+                previous_room: self.previous_room.clone(),
+                seed: Some(self.seed),
+            })
+        } else {
+            Err("error.session_mid_turn".to_owned())
+        }
+    }
+
+    pub fn from_campaign(
+        campaign: Campaign,
+        content: &Content,
+        seed: u64,
+    ) -> Update {
+        let mut gs = GameState::new(content, seed);
+        if !gs.map.map_data.has_area(campaign.ship_area) {
+            return Err(message(
+                "error.unknown_area",
+                &[&campaign.ship_area],
+            ));
+        }
+
+        // Cards kept in hand are taken out of the deck so they can't
+        // be drawn a second time. The deck's copy is used since the
+        // save doesn't keep the card's effects.
+        let mut players = campaign.players;
+        for card in players.iter_mut().flat_map(|p| p.hand.iter_mut())
+        {
+            if let Some(deck_card) =
+                gs.ability_deck.take(|c| c.deck_ix == card.deck_ix)
+            {
+                *card = deck_card;
+            }
+        }
+
+        // Adventure cards on the ship can't be for sale too
+        for card in campaign.adventure_cards.iter() {
+            match gs.market.iter().position(|c| c == card) {
+                Some(ix) => {
+                    gs.market.remove(ix);
+                }
+                None => {
+                    gs.adventure_deck.take(|c| c == card);
+                }
+            }
+        }
+        let gs = gs.refill_market();
+
+        let mut crew = campaign.crew;
+        for member in crew.iter_mut() {
+            member.refresh(&content.crew)?;
+        }
+
+        Ok(GameState {
+            crew,
+            resources: campaign.resources,
+            adventure_cards: campaign.adventure_cards,
+            ship_damage: campaign.ship_damage,
+            turn: campaign.turn,
+            round: campaign.round,
+            active_player: campaign.active_player
+                % players.len().max(1),
+            previous_room: campaign.previous_room,
+            players,
+            map: GameMap {
+                ship_area: campaign.ship_area,
+                ..gs.map.clone()
+            },
+            ..gs
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{
+        adventure_deck::MARKET_SIZE, content::DEFAULT_CONTENT_DIR,
+        game_phase::ShipActionSubphase,
+    };
+
+    fn test_content() -> Content {
+        Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_campaign_round_trip() {
+        let mut gs = GameState::init_state();
+        gs.crew[2].damage = 3;
+        gs.crew[2].fatigue = 1;
+        gs.resources.coins = 4;
+        gs.map.ship_area = 6;
+        gs = gs.draw_cards(0, 1).unwrap();
+
+        let campaign = gs.end_session().unwrap();
+        let json = serde_json::to_string(&campaign).unwrap();
+        let resumed = GameState::from_campaign(
+            serde_json::from_str(&json).unwrap(),
+            &test_content(),
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(resumed.crew[2].damage, 3);
+        assert_eq!(resumed.crew[2].fatigue, 1);
+        assert_eq!(resumed.resources.coins, 4);
+        assert_eq!(resumed.map.ship_area, 6);
+        assert_eq!(resumed.players[0].hand, gs.players[0].hand);
+    }
+
+    #[test]
+    fn test_seed_only_in_campaign() {
+        let gs = GameState::new(&test_content(), 42);
+
+        assert!(serde_json::to_value(&gs)
+            .unwrap()
+            .get("seed")
+            .is_none());
+        assert_eq!(gs.end_session().unwrap().seed, Some(42));
+    }
+
+    #[test]
+    fn test_turns_carry_over() {
+        let mut gs = GameState::init_state();
+        gs.round = 4;
+        gs.turn = 7;
+        gs.previous_room = ShipRoom::Bridge;
+
+        let resumed = GameState::from_campaign(
+            gs.end_session().unwrap(),
+            &test_content(),
+            0,
+        )
+        .unwrap();
+        assert_eq!((resumed.turn, resumed.round), (7, 4));
+        assert_eq!(resumed.previous_room, ShipRoom::Bridge);
+    }
+
+    #[test]
+    fn test_held_cards_are_not_drawn_again() {
+        let content = test_content();
+        let gs = GameState::init_state().draw_cards(0, 1).unwrap();
+        let held = gs.players[0].hand[0].clone();
+
+        // Drawing as many cards as the catalog has empties the deck,
+        // so the hand ends up with every copy once
+        let total =
+            content.ability_cards.iter().map(|e| e.count).sum();
+        let resumed = GameState::from_campaign(
+            gs.end_session().unwrap(),
+            &content,
+            0,
+        )
+        .and_then(|g| g.draw_cards(0, total))
+        .unwrap();
+
+        let entry = content
+            .ability_cards
+            .iter()
+            .find(|e| e.card.deck_ix == held.deck_ix)
+            .unwrap();
+        let copies = resumed.players[0]
+            .hand
+            .iter()
+            .filter(|c| c.deck_ix == held.deck_ix);
+        assert_eq!(copies.count(), entry.count as usize);
+    }
+
+    #[test]
+    fn test_crew_take_the_roster_definition() {
+        let mut campaign =
+            GameState::init_state().end_session().unwrap();
+        campaign.crew[4].max_health = 0;
+        campaign.crew[4].image.clear();
+        campaign.crew[4].damage = 2;
+
+        let resumed =
+            GameState::from_campaign(campaign, &test_content(), 0)
+                .unwrap();
+        assert_eq!(resumed.crew[4].max_health, 5);
+        assert_eq!(resumed.crew[4].image, "crew/sofi_odessa");
+        assert_eq!(resumed.crew[4].damage, 2);
+    }
+
+    #[test]
+    fn test_err_if_unknown_area() {
+        let mut campaign =
+            GameState::init_state().end_session().unwrap();
+        campaign.ship_area = 99;
+
+        assert_eq!(
+            GameState::from_campaign(campaign, &test_content(), 0)
+                .err(),
+            Some(message("error.unknown_area", &[&99]))
+        );
+    }
+
+    #[test]
+    fn test_err_if_not_on_roster() {
+        let mut campaign =
+            GameState::init_state().end_session().unwrap();
+        campaign.crew[1].name = "Nobody".to_owned();
+
+        assert_eq!(
+            GameState::from_campaign(campaign, &test_content(), 0)
+                .err(),
+            Some(message("error.unknown_crew", &[&"Nobody"]))
+        );
+    }
+
+    #[test]
+    fn test_bought_cards_are_not_for_sale() {
+        let mut gs = GameState::init_state();
+        gs.resources.coins = 10;
+        let gs = gs.buy_adventure_card(0).unwrap();
+        let bought = gs.adventure_cards[0].clone();
+
+        let mut resumed = GameState::from_campaign(
+            gs.end_session().unwrap(),
+            &test_content(),
+            0,
+        )
+        .unwrap();
+        assert_eq!(resumed.adventure_cards, vec![bought.clone()]);
+        assert_eq!(resumed.market.len(), MARKET_SIZE);
+        assert!(!resumed.market.contains(&bought));
+        assert!(resumed
+            .adventure_deck
+            .take(|c| *c == bought)
+            .is_none());
+    }
+
+    #[test]
+    fn test_err_if_mid_turn() {
+        let gs = GameState::init_state()
+            .set_phase(GamePhase::ShipActionPhase(Some(
+                ShipActionSubphase::GalleyAction,
+            )))
+            .unwrap();
+
+        assert!(gs.end_session().is_err());
+    }
+}
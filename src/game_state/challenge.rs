@@ -1,16 +1,16 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use super::{skill::Skill, Update, GameState};
+use super::{ability_card_deck::one, effect::Effect, skill::Skill};
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct Challenge {
     pub skill: Skill,
     pub amount: u32,
 
-    #[serde(skip_serializing)]
-    pub if_fail: fn(&GameState) -> Update,
-    #[serde(skip_serializing)]
-    pub if_succeed: fn(&GameState) -> Update
+    #[serde(default, skip_serializing)]
+    pub success: Vec<Effect>,
+    #[serde(default, skip_serializing)]
+    pub failure: Vec<Effect>,
 }
 
 impl Default for Challenge {
@@ -18,8 +18,59 @@ impl Default for Challenge {
         Self {
             skill: Skill::Craft,
             amount: Default::default(),
-            if_fail: |gs| Ok(gs.clone()),
-            if_succeed: |gs| Ok(gs.clone())
+            success: Vec::new(),
+            failure: Vec::new(),
         }
     }
 }
+
+// Drawn when a challenge is resolved, the value is added to the
+// total
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct FateCard {
+    pub value: u32,
+}
+
+// An entry in a pack's fate_cards.json
+#[derive(Clone, Deserialize)]
+pub struct FateCardEntry {
+    #[serde(flatten)]
+    pub card: FateCard,
+    // How many copies are shuffled into the deck
+    #[serde(default = "one")]
+    pub count: u32,
+}
+
+// Shown to the players once the challenge is resolved
+#[derive(Clone, Serialize, PartialEq, Debug)]
+pub struct ChallengeResult {
+    pub fate_card: FateCard,
+    pub crew: u32,
+    pub boost: u32,
+    pub total: u32,
+    pub passed: bool,
+}
+
+pub fn fate_deck(entries: &[FateCardEntry]) -> Vec<FateCard> {
+    entries
+        .iter()
+        .flat_map(|e| (0..e.count).map(|_| e.card.clone()))
+        .collect()
+}
+
+pub fn validate_fate_cards(entries: &[FateCardEntry]) -> Vec<String> {
+    let mut errors: Vec<String> = entries
+        .iter()
+        .filter(|e| e.count == 0)
+        .map(|e| {
+            format!("Fate card {} has a count of 0", e.card.value)
+        })
+        .collect();
+
+    // Challenges can't be resolved without a fate card to draw
+    if fate_deck(entries).is_empty() {
+        errors.push("There are no fate cards".to_owned());
+    }
+
+    errors
+}
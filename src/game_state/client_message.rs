@@ -1,9 +1,221 @@
+use std::fmt;
+
 use serde::Serialize;
 
-use super::AbilityCard;
+use super::{
+    adventure_deck::AdventureCard, game_phase::MainActionSubphase,
+    locale::message, skill::Skill, AbilityCard, Resource,
+    SearchToken, ShipRoom,
+};
 
 #[derive(Clone, Serialize)]
 pub enum ClientMessage {
-    GainCommandPoints { amount: u32 },
-    DrewAbilityCard { card: AbilityCard }
+    GainCommandPoints {
+        amount: u32,
+    },
+    DrewAbilityCard {
+        card: AbilityCard,
+    },
+    PlayedAbilityCard {
+        card: AbilityCard,
+    },
+    CommittedAbilityCard {
+        card: AbilityCard,
+        amount: u32,
+    },
+    TookRoom {
+        room: ShipRoom,
+    },
+    DrewSearchToken {
+        token: SearchToken,
+    },
+    ChoseSearchToken {
+        token: SearchToken,
+    },
+    DrewEventCard {
+        name: String,
+    },
+    ChoseEventOption {
+        text: String,
+    },
+    ChallengeResolved {
+        skill: Skill,
+        total: u32,
+        target: u32,
+    },
+    TookDamage {
+        crew: String,
+        amount: u8,
+    },
+    ShipDamaged {
+        amount: u8,
+    },
+    KnockedOut {
+        crew: String,
+    },
+    Recovered {
+        crew: String,
+    },
+    GameOver {
+        reason: String,
+    },
+    Healed {
+        crew: String,
+        amount: u8,
+    },
+    FatigueChanged {
+        crew: String,
+        amount: i32,
+    },
+    GainedResource {
+        resource: Resource,
+        amount: u32,
+    },
+    LostResource {
+        resource: Resource,
+        amount: u32,
+    },
+    ShipMoved {
+        to_area: u32,
+    },
+    TookMainAction {
+        action: MainActionSubphase,
+    },
+    // The players read the area's entry in the storybook
+    Explored {
+        area: u32,
+        page: u32,
+    },
+    // And read the port's entry
+    Docked {
+        port: String,
+        page: u32,
+    },
+    BoughtAdventureCard {
+        card: AdventureCard,
+    },
+    TurnEnded {
+        turn: u32,
+    },
+    RoundEnded {
+        round: u32,
+    },
+    WentHungry,
+}
+
+// Used to write the transcript. Each event is a message for the
+// string tables.
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ClientMessage::GainCommandPoints { amount } => {
+                message("log.gain_command_points", &[amount])
+            }
+            ClientMessage::DrewAbilityCard { card } => {
+                message("log.drew_ability_card", &[&card.name])
+            }
+            ClientMessage::PlayedAbilityCard { card } => {
+                message("log.played_ability_card", &[&card.name])
+            }
+            ClientMessage::CommittedAbilityCard { card, amount } => {
+                message(
+                    "log.committed_ability_card",
+                    &[&card.name, amount],
+                )
+            }
+            ClientMessage::TookRoom { room } => message(
+                "log.took_room",
+                &[&format!("room.{:?}", room)],
+            ),
+            ClientMessage::DrewSearchToken { token } => {
+                message("log.drew_search_token", &[&token.id])
+            }
+            ClientMessage::ChoseSearchToken { token } => {
+                message("log.chose_search_token", &[&token.id])
+            }
+            ClientMessage::DrewEventCard { name } => {
+                message("log.drew_event_card", &[name])
+            }
+            ClientMessage::ChoseEventOption { text } => {
+                message("log.chose_event_option", &[text])
+            }
+            ClientMessage::ChallengeResolved {
+                skill,
+                total,
+                target,
+            } => {
+                let key = if total >= target {
+                    "log.challenge_passed"
+                } else {
+                    "log.challenge_failed"
+                };
+                let skill = format!("skill.{}", skill);
+                message(key, &[&skill, total, target])
+            }
+            ClientMessage::TookDamage { crew, amount } => {
+                message("log.took_damage", &[crew, amount])
+            }
+            ClientMessage::ShipDamaged { amount } => {
+                message("log.ship_damaged", &[amount])
+            }
+            ClientMessage::KnockedOut { crew } => {
+                message("log.knocked_out", &[crew])
+            }
+            ClientMessage::Recovered { crew } => {
+                message("log.recovered", &[crew])
+            }
+            ClientMessage::GameOver { reason } => {
+                message("log.game_over", &[reason])
+            }
+            ClientMessage::Healed { crew, amount } => {
+                message("log.healed", &[crew, amount])
+            }
+            ClientMessage::FatigueChanged { crew, amount } => {
+                if *amount < 0 {
+                    message("log.lost_fatigue", &[crew, &-amount])
+                } else {
+                    message("log.gained_fatigue", &[crew, amount])
+                }
+            }
+            ClientMessage::GainedResource { resource, amount } => {
+                message(
+                    "log.gained_resource",
+                    &[amount, &format!("resource.{:?}", resource)],
+                )
+            }
+            ClientMessage::LostResource { resource, amount } => {
+                message(
+                    "log.lost_resource",
+                    &[amount, &format!("resource.{:?}", resource)],
+                )
+            }
+            ClientMessage::ShipMoved { to_area } => {
+                message("log.ship_moved", &[to_area])
+            }
+            ClientMessage::TookMainAction { action } => message(
+                "log.took_main_action",
+                &[&format!("main_action.{:?}", action)],
+            ),
+            ClientMessage::Explored { area, page } => {
+                message("log.explored", &[area, page])
+            }
+            ClientMessage::Docked { port, page } => {
+                message("log.docked", &[port, page])
+            }
+            ClientMessage::BoughtAdventureCard { card } => {
+                message("log.bought_adventure_card", &[&card.name])
+            }
+            ClientMessage::TurnEnded { turn } => {
+                message("log.turn_ended", &[turn])
+            }
+            ClientMessage::RoundEnded { round } => {
+                message("log.round_ended", &[round])
+            }
+            ClientMessage::WentHungry => {
+                message("log.went_hungry", &[])
+            }
+        };
+
+        write!(f, "{}", text)
+    }
 }
@@ -0,0 +1,608 @@
+use std::{fs, iter, path::Path};
+
+use serde::de::DeserializeOwned;
+
+use super::{
+    ability_card_deck::{validate_ability_cards, AbilityCardEntry},
+    adventure_deck::{validate_adventure_cards, AdventureCard},
+    challenge::{validate_fate_cards, FateCardEntry},
+    crew::{validate_crew, Crew, CrewEntry},
+    effect::{validate_effects, Effect},
+    event_deck::{validate_event_cards, EventCard},
+    locale::{message, Locales},
+    map::MapData,
+    pack::{
+        available_packs, resolve_packs, Manifest, PackRef, BASE_PACK,
+    },
+    SearchToken,
+};
+
+pub const DEFAULT_CONTENT_DIR: &str = "content";
+
+// Every pack may have any of these files
+const EVENT_CARDS_FILE: &str = "event_cards.json";
+const SEARCH_TOKENS_FILE: &str = "search_tokens.json";
+const ABILITY_CARDS_FILE: &str = "ability_cards.json";
+const ADVENTURE_CARDS_FILE: &str = "adventure_cards.json";
+const FATE_CARDS_FILE: &str = "fate_cards.json";
+const CREW_FILE: &str = "crew.json";
+const AREAS_FILE: &str = "areas.json";
+
+// The game data from the active content packs
+#[derive(Clone)]
+pub struct Content {
+    pub packs: Vec<Manifest>,
+    pub event_cards: Vec<EventCard>,
+    pub search_tokens: Vec<SearchToken>,
+    pub ability_cards: Vec<AbilityCardEntry>,
+    pub adventure_cards: Vec<AdventureCard>,
+    pub fate_cards: Vec<FateCardEntry>,
+    // Every crew member that can join, not only the starting crew
+    pub crew: Vec<CrewEntry>,
+    pub map: MapData,
+    pub locales: Locales,
+}
+
+impl Content {
+    // Loads the base game plus the given packs and their dependencies
+    pub fn load(
+        dir: &str,
+        packs: &[String],
+    ) -> Result<Content, String> {
+        let ids: Vec<String> = iter::once(BASE_PACK.to_owned())
+            .chain(packs.iter().cloned())
+            .collect();
+        let packs = available_packs(dir)
+            .and_then(|available| resolve_packs(&available, &ids))?;
+
+        let (content, mut errors) = read_packs(dir, packs);
+        errors.extend(content.validate());
+
+        if errors.is_empty() {
+            Ok(content)
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    pub fn pack_refs(&self) -> Vec<PackRef> {
+        self.packs.iter().map(PackRef::from).collect()
+    }
+
+    // A campaign can only go on with the pack versions it was
+    // played with. Saves that didn't record a version take whatever
+    // is installed.
+    pub fn check_pack_versions(
+        &self,
+        packs: &[PackRef],
+    ) -> Result<(), String> {
+        for pack in packs.iter().filter(|p| !p.version.is_empty()) {
+            let installed =
+                self.packs.iter().find(|m| m.id == pack.id).ok_or(
+                    message("error.unknown_pack", &[&pack.id]),
+                )?;
+            if installed.version != pack.version {
+                return Err(message(
+                    "error.pack_version",
+                    &[&pack.id, &pack.version, &installed.version],
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = validate_event_cards(&self.event_cards);
+
+        for (ix, token) in self.search_tokens.iter().enumerate() {
+            if self.search_tokens[..ix]
+                .iter()
+                .any(|t| t.id == token.id)
+            {
+                errors.push(format!(
+                    "Search token {} is repeated",
+                    token.id
+                ));
+            }
+            errors.extend(validate_effects(
+                &token.effects,
+                &format!("Search token {}", token.id),
+                false,
+            ));
+        }
+
+        errors.extend(validate_crew(&self.crew));
+        errors.extend(validate_ability_cards(&self.ability_cards));
+        errors
+            .extend(validate_adventure_cards(&self.adventure_cards));
+        errors.extend(validate_fate_cards(&self.fate_cards));
+        errors.extend(self.map.validate());
+        errors.extend(self.validate_effect_areas());
+        errors.extend(self.locales.missing_keys(
+            self.text_keys().iter().map(|k| k.as_str()),
+        ));
+
+        errors
+    }
+
+    pub fn starting_crew(&self) -> Vec<Crew> {
+        self.crew
+            .iter()
+            .filter(|e| e.starting)
+            .map(|e| e.crew.clone())
+            .collect()
+    }
+
+    // The crew chosen at setup in the given order, or the starting
+    // crew if none were chosen
+    pub fn choose_crew(
+        &self,
+        names: &[String],
+    ) -> Result<Vec<Crew>, String> {
+        if names.is_empty() {
+            return Ok(self.starting_crew());
+        }
+
+        for (ix, name) in names.iter().enumerate() {
+            if names[..ix].contains(name) {
+                return Err(message("error.repeated_crew", &[name]));
+            }
+        }
+        names
+            .iter()
+            .map(|name| {
+                self.crew
+                    .iter()
+                    .find(|e| &e.crew.name == name)
+                    .map(|e| e.crew.clone())
+                    .ok_or(message("error.unknown_crew", &[name]))
+            })
+            .collect()
+    }
+
+    // Every piece of card text, which should all be string table keys
+    // Every effect in the content, with where it comes from
+    fn effects(&self) -> Vec<(String, Effect)> {
+        let mut effects = Vec::new();
+
+        for card in self.event_cards.iter() {
+            for option in card.options.iter() {
+                let source = format!(
+                    "Option \"{}\" of {}",
+                    option.text, card.name
+                );
+                effects.extend(
+                    option
+                        .effects
+                        .iter()
+                        .cloned()
+                        .chain(
+                            option
+                                .challenge
+                                .clone()
+                                .map(Effect::Challenge),
+                        )
+                        .map(|e| (source.clone(), e)),
+                );
+            }
+        }
+        for token in self.search_tokens.iter() {
+            let source = format!("Search token {}", token.id);
+            effects.extend(
+                token
+                    .effects
+                    .iter()
+                    .map(|e| (source.clone(), e.clone())),
+            );
+        }
+        for entry in self.ability_cards.iter() {
+            let source = format!("Ability card {}", entry.card.name);
+            effects.extend(
+                entry
+                    .card
+                    .effects
+                    .iter()
+                    .map(|e| (source.clone(), e.clone())),
+            );
+        }
+
+        effects
+    }
+
+    // Effects can only move the ship to, or check for, areas on the
+    // map
+    fn validate_effect_areas(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for (source, effect) in self.effects() {
+            effect.visit(&mut |e| {
+                if let Some(area) =
+                    e.area().filter(|a| !self.map.has_area(*a))
+                {
+                    errors.push(format!(
+                        "{} refers to unknown area {}",
+                        source, area
+                    ));
+                }
+            });
+        }
+
+        errors
+    }
+
+    fn text_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+
+        for card in self.event_cards.iter() {
+            keys.push(card.name.clone());
+            keys.extend(card.options.iter().map(|o| o.text.clone()));
+        }
+        for entry in self.crew.iter() {
+            keys.extend(entry.crew.abilities.iter().cloned());
+        }
+        keys.extend(
+            self.ability_cards.iter().map(|e| e.card.name.clone()),
+        );
+
+        keys.extend(
+            self.adventure_cards.iter().map(|c| c.name.clone()),
+        );
+        keys.extend(self.map.ports().map(|p| p.name.clone()));
+
+        for (_, effect) in self.effects() {
+            effect.visit(&mut |e| {
+                if let Effect::Choice { options } = e {
+                    keys.extend(
+                        options.iter().map(|o| o.text.clone()),
+                    );
+                }
+            });
+        }
+
+        keys
+    }
+}
+
+// Checks every pack in the content directory together, and keeps
+// going after a file fails to parse so every problem is reported at
+// once
+pub fn check_content(dir: &str) -> Vec<String> {
+    let packs = available_packs(dir).and_then(|available| {
+        let ids: Vec<String> =
+            available.iter().map(|m| m.id.clone()).collect();
+        resolve_packs(&available, &ids)
+    });
+
+    match packs {
+        Ok(packs) => {
+            let (content, mut errors) = read_packs(dir, packs);
+            errors.extend(content.validate());
+            errors
+        }
+        Err(err) => vec![err],
+    }
+}
+
+// Packs are read in order, each adding to what came before
+fn read_packs(
+    dir: &str,
+    packs: Vec<Manifest>,
+) -> (Content, Vec<String>) {
+    let mut content = Content {
+        packs: Vec::new(),
+        event_cards: Vec::new(),
+        search_tokens: Vec::new(),
+        ability_cards: Vec::new(),
+        adventure_cards: Vec::new(),
+        fate_cards: Vec::new(),
+        crew: Vec::new(),
+        map: MapData::default(),
+        locales: Locales::default(),
+    };
+    let mut errors = Vec::new();
+
+    for pack in packs {
+        let pack_dir = Path::new(dir).join(&pack.id);
+
+        match read_file::<Vec<_>>(&pack_dir, EVENT_CARDS_FILE) {
+            Ok(cards) => content.event_cards.extend(cards),
+            Err(err) => errors.push(err),
+        }
+        match read_file::<Vec<_>>(&pack_dir, SEARCH_TOKENS_FILE) {
+            Ok(tokens) => content.search_tokens.extend(tokens),
+            Err(err) => errors.push(err),
+        }
+        match read_file::<Vec<_>>(&pack_dir, ABILITY_CARDS_FILE) {
+            Ok(cards) => content.ability_cards.extend(cards),
+            Err(err) => errors.push(err),
+        }
+        match read_file::<Vec<_>>(&pack_dir, ADVENTURE_CARDS_FILE) {
+            Ok(cards) => content.adventure_cards.extend(cards),
+            Err(err) => errors.push(err),
+        }
+        match read_file::<Vec<_>>(&pack_dir, FATE_CARDS_FILE) {
+            Ok(cards) => content.fate_cards.extend(cards),
+            Err(err) => errors.push(err),
+        }
+        match read_file::<Vec<_>>(&pack_dir, CREW_FILE) {
+            Ok(crew) => content.crew.extend(crew),
+            Err(err) => errors.push(err),
+        }
+        match read_file::<Vec<_>>(&pack_dir, AREAS_FILE) {
+            Ok(areas) => errors.extend(content.map.add_areas(&areas)),
+            Err(err) => errors.push(err),
+        }
+
+        errors.extend(content.locales.read_pack(&pack_dir));
+
+        content.packs.push(pack);
+    }
+
+    (content, errors)
+}
+
+// A pack doesn't have to have every file
+fn read_file<T: DeserializeOwned + Default>(
+    dir: &Path,
+    file: &str,
+) -> Result<T, String> {
+    let path = dir.join(file);
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    fs::read_to_string(&path)
+        .map_err(|e| {
+            format!("Could not read {}: {}", path.display(), e)
+        })
+        .and_then(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| format!("{}: {}", path.display(), e))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::game_state::{
+        challenge::Challenge, effect::Condition, skill::Skill,
+    };
+
+    static DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    // A content directory with a base pack that only has the start
+    // area and the given files in an "islands" pack
+    fn temp_content(files: &[(&str, &str)]) -> String {
+        let dir = std::env::temp_dir().join(format!(
+            "sleeping_gods_content_{}_{}",
+            std::process::id(),
+            DIR_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+
+        let manifests = [
+            ("base", r#"{ "id": "base", "version": "1.0.0" }"#),
+            (
+                "islands",
+                r#"{ "id": "islands", "version": "0.1.0",
+                     "dependencies": ["base"] }"#,
+            ),
+        ];
+        for (id, manifest) in manifests {
+            fs::create_dir_all(dir.join(id)).unwrap();
+            fs::write(dir.join(id).join("manifest.json"), manifest)
+                .unwrap();
+        }
+        fs::write(
+            dir.join("base").join(AREAS_FILE),
+            r#"[{ "id": 1, "region": 1, "page": 1 }]"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("base").join(FATE_CARDS_FILE),
+            r#"[{ "value": 1 }]"#,
+        )
+        .unwrap();
+        for (file, json) in files {
+            let path = dir.join("islands").join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, json).unwrap();
+        }
+
+        dir.to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn test_load_content() {
+        let content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        assert!(!content.event_cards.is_empty());
+        assert_eq!(content.pack_refs()[0].id, BASE_PACK);
+    }
+
+    #[test]
+    fn test_load_pack() {
+        let dir = temp_content(&[
+            (
+                EVENT_CARDS_FILE,
+                r#"[{ "name": "event.reef", "deck_index": 40,
+                      "options": [{ "text": "event.reef.avoid" }] }]"#,
+            ),
+            (
+                "locales/en.json",
+                r#"{ "event.reef": "Reef",
+                     "event.reef.avoid": "Steer clear" }"#,
+            ),
+            (
+                AREAS_FILE,
+                r#"[{ "id": 8, "region": 2, "terrain": "Coast",
+                      "page": 3, "adjacent": [1] }]"#,
+            ),
+        ]);
+
+        let base = Content::load(&dir, &[]).unwrap();
+        assert!(base.event_cards.is_empty());
+
+        let content =
+            Content::load(&dir, &["islands".to_owned()]).unwrap();
+        assert_eq!(
+            content
+                .locales
+                .render("en", &content.event_cards[0].name),
+            "Reef"
+        );
+        assert_eq!(
+            content.pack_refs(),
+            vec![
+                PackRef {
+                    id: "base".to_owned(),
+                    version: "1.0.0".to_owned()
+                },
+                PackRef {
+                    id: "islands".to_owned(),
+                    version: "0.1.0".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pack_versions() {
+        let content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        let mut packs = content.pack_refs();
+        assert!(content.check_pack_versions(&packs).is_ok());
+
+        packs[0].version.clear();
+        assert!(content.check_pack_versions(&packs).is_ok());
+
+        packs[0].version = "0.0.1".to_owned();
+        assert!(content.check_pack_versions(&packs).is_err());
+
+        packs[0].id = "monsters".to_owned();
+        assert_eq!(
+            content.check_pack_versions(&packs),
+            Err(message("error.unknown_pack", &[&"monsters"]))
+        );
+    }
+
+    #[test]
+    fn test_unknown_pack() {
+        let dir = temp_content(&[]);
+        assert!(
+            Content::load(&dir, &["monsters".to_owned()]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_duplicate_deck_index() {
+        let mut content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        let card = content.event_cards[0].clone();
+        content.event_cards.push(card);
+
+        assert_eq!(content.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_effect_with_unknown_area() {
+        let mut content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        content.search_tokens[0].effects.push(Effect::Conditional {
+            condition: Condition::ShipInArea { area: 99 },
+            then: vec![Effect::MoveShip { to_area: 98 }],
+            otherwise: Vec::new(),
+        });
+
+        assert_eq!(
+            content.validate(),
+            vec![
+                "Search token 1 refers to unknown area 99",
+                "Search token 1 refers to unknown area 98",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_reports_every_file() {
+        let dir = temp_content(&[
+            (EVENT_CARDS_FILE, "["),
+            (CREW_FILE, r#"[{ "name": "Sofi Odessa" }]"#),
+            (AREAS_FILE, r#"[{ "id": 9, "region": 3, "page": 5 }]"#),
+            (
+                SEARCH_TOKENS_FILE,
+                r#"[{ "id": 20, "effects": [{ "type": "choice",
+                      "options": [{ "text": "token.20.keep",
+                                   "effects": [] }] }] }]"#,
+            ),
+        ]);
+
+        let errors = check_content(&dir);
+        assert_eq!(errors.len(), 4);
+        assert!(errors[2].starts_with("Area 9 can't be reached"));
+        assert_eq!(errors[3], "No en text for token.20.keep");
+    }
+
+    #[test]
+    fn test_choose_crew() {
+        let content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        let names =
+            vec!["Marco Reyes".to_owned(), "Sofi Odessa".to_owned()];
+
+        let crew = content.choose_crew(&names).unwrap();
+        assert_eq!(crew[0].name, "Marco Reyes");
+        assert_eq!(crew[0].skill(Skill::Wits), 1);
+        assert_eq!(crew[1].max_health, 5);
+
+        assert_eq!(
+            content.choose_crew(&[]).unwrap().len(),
+            content.crew.len()
+        );
+        assert!(content
+            .choose_crew(&vec!["Sofi Odessa".to_owned(); 2])
+            .is_err());
+        assert!(content.choose_crew(&["Nemo".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn test_recruits_dont_start() {
+        let dir = temp_content(&[(
+            CREW_FILE,
+            r#"[{ "name": "Ali Haddad", "max_health": 3,
+                  "skills": { "Savvy": 2 }, "starting": false }]"#,
+        )]);
+
+        let content =
+            Content::load(&dir, &["islands".to_owned()]).unwrap();
+        assert!(content.starting_crew().is_empty());
+        assert_eq!(
+            content.choose_crew(&["Ali Haddad".to_owned()]).unwrap()
+                [0]
+            .skill(Skill::Craft),
+            0
+        );
+    }
+
+    #[test]
+    fn test_empty_fate_deck() {
+        let mut content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        content.fate_cards.clear();
+
+        assert_eq!(content.validate().len(), 1);
+    }
+
+    #[test]
+    fn test_zero_challenge() {
+        let mut content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+        content.event_cards[0].options[0].challenge =
+            Some(Challenge::default());
+
+        assert_eq!(content.validate().len(), 1);
+    }
+}
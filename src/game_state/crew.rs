@@ -1,49 +1,114 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_with::DisplayFromStr;
 
-use super::skill::Skill;
+use super::{locale::message, skill::Skill};
 use std::collections::HashMap;
 
+// A crew member at this much fatigue is exhausted
+pub const MAX_FATIGUE: u8 = 2;
+
 #[serde_with::serde_as]
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Crew {
     pub name: String,
+    #[serde(default)]
     pub fatigue: u8,
+    #[serde(default)]
     pub damage: u8,
     #[serde_as(as = "HashMap<DisplayFromStr,_>")]
     pub skills: HashMap<Skill, u32>,
+    // Saves from before the roster was in the content files don't
+    // have these, they're filled in from the roster on resuming
+    #[serde(default)]
+    pub max_health: u8,
+    // The game is lost if the captain is knocked out
+    #[serde(default)]
+    pub captain: bool,
+    // String table keys for the text of each special ability
+    #[serde(default)]
+    pub abilities: Vec<String>,
+    // The client looks up the portrait with this
+    #[serde(default)]
+    pub image: String,
+}
+
+// An entry in a pack's crew.json
+#[derive(Clone, Deserialize)]
+pub struct CrewEntry {
+    #[serde(flatten)]
+    pub crew: Crew,
+    // Whether the crew member is on the ship when a game starts
+    // without choosing the crew
+    #[serde(default = "yes")]
+    pub starting: bool,
+}
+
+fn yes() -> bool {
+    true
 }
 
 impl Crew {
-    pub fn new(
-        name: &str,
-        savvy: u32,
-        craft: u32,
-        strength: u32,
-        wits: u32,
-        perception: u32,
-    ) -> Self {
-        let mut skills = HashMap::new();
-
-        skills.insert(Skill::Craft, craft);
-        skills.insert(Skill::Savvy, savvy);
-        skills.insert(Skill::Strength, strength);
-        skills.insert(Skill::Perception, perception);
-        skills.insert(Skill::Wits, wits);
+    pub fn skill(&self, skill: Skill) -> u32 {
+        self.skills.get(&skill).copied().unwrap_or(0)
+    }
 
-        Crew {
-            name: name.to_owned(),
-            fatigue: 0,
-            damage: 0,
-            skills,
-        }
+    pub fn is_exhausted(&self) -> bool {
+        self.fatigue >= MAX_FATIGUE
+    }
+
+    pub fn is_knocked_out(&self) -> bool {
+        self.damage >= self.max_health
+    }
+
+    // Badly hurt once half their health is gone
+    pub fn is_wounded(&self) -> bool {
+        self.damage > 0
+            && u16::from(self.damage) * 2
+                >= u16::from(self.max_health)
+    }
+
+    // What the crew member adds to a challenge: each point of
+    // fatigue and being wounded take one off their skill
+    pub fn contribution(&self, skill: Skill) -> u32 {
+        let penalty =
+            u32::from(self.fatigue) + u32::from(self.is_wounded());
+        self.skill(skill).saturating_sub(penalty)
+    }
+
+    // Takes the roster's definition, keeping what happened to the
+    // crew member during the campaign
+    pub fn refresh(
+        &mut self,
+        roster: &[CrewEntry],
+    ) -> Result<(), String> {
+        let entry = roster
+            .iter()
+            .find(|e| e.crew.name == self.name)
+            .ok_or(message("error.unknown_crew", &[&self.name]))?;
+
+        *self = Crew {
+            fatigue: self.fatigue,
+            damage: self.damage.min(entry.crew.max_health),
+            ..entry.crew.clone()
+        };
+        Ok(())
+    }
+
+    // Damage stops at the crew member's health
+    pub fn take_damage(&mut self, amount: u8) {
+        self.damage =
+            self.damage.saturating_add(amount).min(self.max_health);
+    }
+
+    pub fn heal(&mut self, amount: u8) {
+        self.damage = self.damage.saturating_sub(amount);
     }
 
     pub fn change_fatigue(&mut self, amount: i32) {
         let mut fatigue = i32::from(self.fatigue) + amount;
-        // Clamp fatigue to [0, 2]
-        if fatigue > 2 {
-            fatigue = 2;
+        // Clamp fatigue to [0, MAX_FATIGUE]
+        if fatigue > i32::from(MAX_FATIGUE) {
+            fatigue = i32::from(MAX_FATIGUE);
         } else if fatigue < 0 {
             fatigue = 0;
         }
@@ -51,3 +116,77 @@ impl Crew {
         self.fatigue = u8::try_from(fatigue).unwrap_or(0);
     }
 }
+
+pub fn validate_crew(roster: &[CrewEntry]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (ix, entry) in roster.iter().enumerate() {
+        let crew = &entry.crew;
+        if roster[..ix].iter().any(|e| e.crew.name == crew.name) {
+            errors.push(format!("Crew {} is repeated", crew.name));
+        }
+        if crew.max_health == 0 {
+            errors.push(format!("Crew {} has no health", crew.name));
+        }
+    }
+    if roster.iter().filter(|e| e.crew.captain).count() > 1 {
+        errors.push("There is more than one captain".to_owned());
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn crew(fatigue: u8, damage: u8) -> Crew {
+        Crew {
+            name: "crew.test".to_owned(),
+            fatigue,
+            damage,
+            skills: HashMap::from([(Skill::Savvy, 3)]),
+            max_health: 4,
+            captain: false,
+            abilities: Vec::new(),
+            image: String::new(),
+        }
+    }
+
+    #[test_case(0, 0, 3; "Fresh")]
+    #[test_case(1, 0, 2; "Tired")]
+    #[test_case(0, 1, 3; "Scratched")]
+    #[test_case(0, 2, 2; "Wounded")]
+    #[test_case(1, 3, 1; "Tired and wounded")]
+    fn test_contribution(fatigue: u8, damage: u8, expected: u32) {
+        assert_eq!(
+            crew(fatigue, damage).contribution(Skill::Savvy),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_missing_skill_never_negative() {
+        assert_eq!(crew(1, 3).contribution(Skill::Wits), 0);
+    }
+
+    #[test]
+    fn test_damage_stops_at_health() {
+        let mut crew = crew(0, 3);
+        crew.take_damage(u8::MAX);
+        assert_eq!(crew.damage, 4);
+
+        crew.heal(1);
+        assert!(!crew.is_knocked_out());
+    }
+
+    #[test]
+    fn test_limits() {
+        assert!(crew(MAX_FATIGUE, 0).is_exhausted());
+        assert!(!crew(1, 0).is_exhausted());
+        assert!(crew(0, 4).is_knocked_out());
+        assert!(!crew(0, 3).is_knocked_out());
+    }
+}
@@ -1,3 +1,4 @@
+use rand::{seq::SliceRandom, Rng};
 use serde::Serialize;
 
 #[derive(Serialize, Clone)]
@@ -7,25 +8,93 @@ pub struct Deck<T: Clone> {
 }
 
 impl<T: Clone> Deck<T> {
-    pub fn new(items: Vec<T>) -> Self {
-        Deck {
+    pub fn new<R: Rng>(items: Vec<T>, rng: &mut R) -> Self {
+        let mut deck = Deck {
             items,
             discard: Vec::new(),
-        }
+        };
+        deck.items.shuffle(rng);
+        deck
     }
 
-    pub fn draw(&mut self) -> Result<T, String> {
+    // The discard is shuffled back in once the deck runs out
+    pub fn draw<R: Rng>(&mut self, rng: &mut R) -> Result<T, String> {
         if self.items.is_empty() {
             self.items.append(&mut self.discard);
-            println!("none left");
+            self.items.shuffle(rng);
         }
 
-        self.items
-            .pop()
-            .ok_or("No items left in the deck".to_string())
+        self.items.pop().ok_or("error.deck_empty".to_string())
+    }
+
+    // Left to draw before the discard is shuffled back in
+    pub fn remaining(&self) -> usize {
+        self.items.len()
     }
 
     pub fn add_to_discard(&mut self, item: &T) {
         self.discard.push(item.clone());
     }
+
+    // Takes the first matching item out of the deck or discard,
+    // e.g. when a card is already in someone's hand
+    pub fn take(&mut self, pred: impl Fn(&T) -> bool) -> Option<T> {
+        if let Some(ix) = self.items.iter().position(&pred) {
+            Some(self.items.remove(ix))
+        } else {
+            let ix = self.discard.iter().position(&pred)?;
+            Some(self.discard.remove(ix))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game_state::GameRng;
+
+    fn draw_all(deck: &mut Deck<u32>, rng: &mut GameRng) -> Vec<u32> {
+        (0..10).map(|_| deck.draw(rng).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_order() {
+        let mut rng1 = GameRng::seed_from_u64(7);
+        let mut rng2 = GameRng::seed_from_u64(7);
+        let mut deck1 = Deck::new((0..10).collect(), &mut rng1);
+        let mut deck2 = Deck::new((0..10).collect(), &mut rng2);
+
+        assert_eq!(
+            draw_all(&mut deck1, &mut rng1),
+            draw_all(&mut deck2, &mut rng2)
+        );
+    }
+
+    #[test]
+    fn test_shuffled() {
+        let mut rng = GameRng::seed_from_u64(7);
+        let mut deck = Deck::new((0..10).collect(), &mut rng);
+
+        let drawn = draw_all(&mut deck, &mut rng);
+        assert_ne!(drawn, (0..10).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_discard_reshuffled() {
+        let mut rng = GameRng::seed_from_u64(7);
+        let mut deck = Deck::new((0..10).collect(), &mut rng);
+
+        let first = draw_all(&mut deck, &mut rng);
+        for card in first.iter() {
+            deck.add_to_discard(card);
+        }
+        let second = draw_all(&mut deck, &mut rng);
+
+        let mut sorted = second.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+        assert_ne!(first, second);
+    }
 }
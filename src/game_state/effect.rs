@@ -0,0 +1,552 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    challenge::Challenge,
+    game_phase::GamePhase,
+    script::{check_script, run_script},
+    GameState, Resource, Update,
+};
+
+// The outcome of a card, as written in the content files.
+// Effects that target a crew member use the crew from the context.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Effect {
+    GainResource {
+        resource: Resource,
+        amount: u32,
+    },
+    LoseResource {
+        resource: Resource,
+        amount: u32,
+    },
+    // Shared out by the players, see DistributeDamageAction
+    TakeDamage {
+        amount: u8,
+        // Whether the ship can take some of it
+        #[serde(default)]
+        ship: bool,
+    },
+    Heal {
+        amount: u8,
+    },
+    AddFatigue {
+        amount: u8,
+    },
+    RemoveFatigue {
+        amount: u8,
+    },
+    DrawAbilityCards {
+        amount: u32,
+    },
+    GiveCommandTokens {
+        amount: u32,
+    },
+    Challenge(Challenge),
+    // Adds to the total of the challenge being played
+    BoostChallenge {
+        amount: u32,
+    },
+    MoveShip {
+        to_area: u32,
+    },
+    Sequence {
+        effects: Vec<Effect>,
+    },
+    // Lets the players pick one of the options before play continues
+    Choice {
+        options: Vec<ChoiceOption>,
+    },
+    Conditional {
+        condition: Condition,
+        #[serde(default)]
+        then: Vec<Effect>,
+        #[serde(default)]
+        otherwise: Vec<Effect>,
+    },
+    // A Rhai script that works out which effects to apply
+    Script {
+        script: String,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct ChoiceOption {
+    pub text: String,
+    #[serde(skip_serializing)]
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Condition {
+    HasResource { resource: Resource, amount: u32 },
+    ShipInArea { area: u32 },
+}
+
+// Who an effect applies to
+#[derive(Clone, Default)]
+pub struct EffectContext {
+    pub player_ix: usize,
+    pub crew_ix: Option<usize>,
+    // The crew committed to a challenge, for its outcome
+    pub challenge_crew: Option<Vec<usize>>,
+}
+
+impl EffectContext {
+    pub fn player(player_ix: usize) -> Self {
+        EffectContext {
+            player_ix,
+            ..EffectContext::default()
+        }
+    }
+
+    pub fn challenge(crew: Vec<usize>) -> Self {
+        EffectContext {
+            challenge_crew: Some(crew),
+            ..EffectContext::default()
+        }
+    }
+
+    // A challenge outcome lands on everyone who took part, which
+    // can be no one at all
+    fn crew(&self) -> Result<Vec<usize>, String> {
+        match (self.crew_ix, &self.challenge_crew) {
+            (Some(ix), _) => Ok(vec![ix]),
+            (None, Some(crew)) => Ok(crew.clone()),
+            (None, None) => Err("error.choose_crew".to_owned()),
+        }
+    }
+}
+
+impl Effect {
+    pub fn apply(
+        &self,
+        state: &GameState,
+        ctx: &EffectContext,
+    ) -> Update {
+        let gs = state.clone();
+        match self {
+            Effect::GainResource { resource, amount } => {
+                gs.gain_resource(*resource, *amount)
+            }
+            Effect::LoseResource { resource, amount } => {
+                gs.lose_resource(*resource, *amount)
+            }
+            Effect::TakeDamage { amount, ship } => {
+                gs.take_damage(*amount, *ship)
+            }
+            Effect::Heal { amount } => ctx
+                .crew()?
+                .iter()
+                .try_fold(gs, |g, ix| g.heal(*ix, *amount)),
+            Effect::AddFatigue { amount } => {
+                ctx.crew()?.iter().try_fold(gs, |g, ix| {
+                    g.change_fatigue(*ix, i32::from(*amount))
+                })
+            }
+            Effect::RemoveFatigue { amount } => {
+                ctx.crew()?.iter().try_fold(gs, |g, ix| {
+                    g.change_fatigue(*ix, -i32::from(*amount))
+                })
+            }
+            Effect::DrawAbilityCards { amount } => {
+                gs.draw_cards(ctx.player_ix, *amount)
+            }
+            Effect::GiveCommandTokens { amount } => {
+                gs.give_command_tokens(ctx.player_ix, *amount)
+            }
+            Effect::Challenge(challenge) => {
+                gs.challenge(challenge.clone())
+            }
+            Effect::BoostChallenge { amount } => {
+                gs.boost_challenge(*amount)
+            }
+            Effect::MoveShip { to_area } => gs.move_ship(*to_area),
+            Effect::Sequence { effects } => {
+                apply_effects(effects, &gs, ctx)
+            }
+            Effect::Choice { options } => Ok(gs
+                .push_phase(GamePhase::ChoicePhase(options.clone()))),
+            Effect::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if condition.holds(&gs) {
+                    apply_effects(then, &gs, ctx)
+                } else {
+                    apply_effects(otherwise, &gs, ctx)
+                }
+            }
+            Effect::Script { script } => run_script(script, &gs, ctx)
+                .and_then(|effects| {
+                    apply_effects(&effects, &gs, ctx)
+                }),
+        }
+    }
+
+    // The area the effect moves the ship to or checks for
+    pub fn area(&self) -> Option<u32> {
+        match self {
+            Effect::MoveShip { to_area } => Some(*to_area),
+            Effect::Conditional {
+                condition: Condition::ShipInArea { area },
+                ..
+            } => Some(*area),
+            _ => None,
+        }
+    }
+
+    // Calls f on this effect and every effect nested in it
+    pub fn visit(&self, f: &mut impl FnMut(&Effect)) {
+        f(self);
+
+        let nested: Vec<&Effect> = match self {
+            Effect::Challenge(challenge) => challenge
+                .success
+                .iter()
+                .chain(challenge.failure.iter())
+                .collect(),
+            Effect::Sequence { effects } => effects.iter().collect(),
+            Effect::Choice { options } => options
+                .iter()
+                .flat_map(|o| o.effects.iter())
+                .collect(),
+            Effect::Conditional {
+                then, otherwise, ..
+            } => then.iter().chain(otherwise.iter()).collect(),
+            _ => Vec::new(),
+        };
+
+        for effect in nested {
+            effect.visit(f);
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, state: &GameState) -> bool {
+        match self {
+            Condition::HasResource { resource, amount } => {
+                state.resources.get(*resource) >= *amount
+            }
+            Condition::ShipInArea { area } => {
+                state.map.ship_area == *area
+            }
+        }
+    }
+}
+
+// Problems with effects that parse but can't work in a game.
+// crew_target is whether the players name a crew member when the
+// effects are applied.
+pub fn validate_effects(
+    effects: &[Effect],
+    source: &str,
+    crew_target: bool,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    check_crew_targets(effects, source, crew_target, &mut errors);
+
+    for effect in effects {
+        effect.visit(&mut |e| match e {
+            Effect::Challenge(Challenge { amount: 0, .. }) => errors
+                .push(format!("{} has a challenge of 0", source)),
+            Effect::Choice { options } if options.is_empty() => {
+                errors.push(format!(
+                    "{} has a choice without options",
+                    source
+                ))
+            }
+            Effect::Script { script } => {
+                if let Err(err) = check_script(script) {
+                    errors.push(format!(
+                        "{} has a broken script: {}",
+                        source, err
+                    ))
+                }
+            }
+            _ => (),
+        });
+    }
+
+    errors
+}
+
+// Crew effects with nothing to land on would stop the game
+fn check_crew_targets(
+    effects: &[Effect],
+    source: &str,
+    crew_target: bool,
+    errors: &mut Vec<String>,
+) {
+    for effect in effects {
+        match effect {
+            Effect::Heal { .. }
+            | Effect::AddFatigue { .. }
+            | Effect::RemoveFatigue { .. }
+                if !crew_target =>
+            {
+                errors.push(format!(
+                    "{} has a crew effect without a crew member",
+                    source
+                ))
+            }
+            Effect::Challenge(challenge) => {
+                check_crew_targets(
+                    &challenge.success,
+                    source,
+                    true,
+                    errors,
+                );
+                check_crew_targets(
+                    &challenge.failure,
+                    source,
+                    true,
+                    errors,
+                );
+            }
+            Effect::Choice { options } => {
+                for option in options {
+                    check_crew_targets(
+                        &option.effects,
+                        source,
+                        true,
+                        errors,
+                    );
+                }
+            }
+            Effect::Sequence { effects } => check_crew_targets(
+                effects,
+                source,
+                crew_target,
+                errors,
+            ),
+            Effect::Conditional {
+                then, otherwise, ..
+            } => {
+                check_crew_targets(then, source, crew_target, errors);
+                check_crew_targets(
+                    otherwise,
+                    source,
+                    crew_target,
+                    errors,
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+pub fn apply_effects(
+    effects: &[Effect],
+    state: &GameState,
+    ctx: &EffectContext,
+) -> Update {
+    effects
+        .iter()
+        .try_fold(state.clone(), |gs, effect| effect.apply(&gs, ctx))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gain(resource: Resource, amount: u32) -> Effect {
+        Effect::GainResource { resource, amount }
+    }
+
+    #[test]
+    fn test_sequence() {
+        let effect = Effect::Sequence {
+            effects: vec![
+                gain(Resource::Coins, 3),
+                Effect::LoseResource {
+                    resource: Resource::Coins,
+                    amount: 1,
+                },
+            ],
+        };
+
+        let gs = effect
+            .apply(
+                &GameState::init_state(),
+                &EffectContext::default(),
+            )
+            .unwrap();
+        assert_eq!(gs.resources.coins, 2);
+    }
+
+    #[test]
+    fn test_lose_more_than_owned() {
+        let effect = Effect::LoseResource {
+            resource: Resource::Meat,
+            amount: 4,
+        };
+
+        let gs = effect
+            .apply(
+                &GameState::init_state(),
+                &EffectContext::default(),
+            )
+            .unwrap();
+        assert_eq!(gs.resources.meat, 0);
+    }
+
+    #[test]
+    fn test_conditional() {
+        let effect = Effect::Conditional {
+            condition: Condition::HasResource {
+                resource: Resource::Coins,
+                amount: 1,
+            },
+            then: vec![gain(Resource::Meat, 1)],
+            otherwise: vec![gain(Resource::Grain, 1)],
+        };
+        let ctx = EffectContext::default();
+
+        let poor =
+            effect.apply(&GameState::init_state(), &ctx).unwrap();
+        assert_eq!(
+            (poor.resources.meat, poor.resources.grain),
+            (0, 1)
+        );
+
+        let rich = gain(Resource::Coins, 1)
+            .apply(&GameState::init_state(), &ctx)
+            .and_then(|g| effect.apply(&g, &ctx))
+            .unwrap();
+        assert_eq!(
+            (rich.resources.meat, rich.resources.grain),
+            (1, 0)
+        );
+    }
+
+    #[test]
+    fn test_crew_effects_need_a_target() {
+        let mut gs = GameState::init_state();
+        gs.crew[1].damage = 3;
+        gs.crew[1].fatigue = 2;
+        let heal = Effect::Heal { amount: 2 };
+        let rest = Effect::RemoveFatigue { amount: 1 };
+
+        assert!(heal.apply(&gs, &EffectContext::default()).is_err());
+
+        let ctx = EffectContext {
+            crew_ix: Some(1),
+            ..EffectContext::default()
+        };
+        let gs = apply_effects(&[heal, rest], &gs, &ctx).unwrap();
+        assert_eq!(gs.crew[1].damage, 1);
+        assert_eq!(gs.crew[1].fatigue, 1);
+    }
+
+    #[test]
+    fn test_challenge_outcome_lands_on_committed_crew() {
+        let gs = GameState::init_state();
+        let tire = Effect::AddFatigue { amount: 1 };
+
+        let ctx = EffectContext::challenge(vec![0, 2]);
+        let tired = tire.apply(&gs, &ctx).unwrap();
+        let fatigue: Vec<u8> =
+            tired.crew.iter().map(|c| c.fatigue).collect();
+        assert_eq!(fatigue[..3], [1, 0, 1]);
+
+        let ctx = EffectContext::challenge(Vec::new());
+        assert!(tire.apply(&gs, &ctx).is_ok());
+    }
+
+    #[test]
+    fn test_validate_crew_targets() {
+        let heal = Effect::Heal { amount: 1 };
+        let in_challenge = Effect::Challenge(Challenge {
+            amount: 3,
+            failure: vec![heal.clone()],
+            ..Challenge::default()
+        });
+        let in_sequence = Effect::Sequence {
+            effects: vec![heal.clone()],
+        };
+
+        assert!(validate_effects(&[in_challenge], "Test", false)
+            .is_empty());
+        assert_eq!(
+            validate_effects(&[in_sequence], "Test", false).len(),
+            1
+        );
+        assert!(validate_effects(&[heal], "Test", true).is_empty());
+    }
+
+    #[test]
+    fn test_choice_opens_phase() {
+        let effect = Effect::Choice {
+            options: vec![ChoiceOption {
+                text: "Take the coin".to_owned(),
+                effects: vec![gain(Resource::Coins, 1)],
+            }],
+        };
+
+        let gs = effect
+            .apply(
+                &GameState::init_state(),
+                &EffectContext::default(),
+            )
+            .unwrap();
+        assert!(matches!(gs.phase(), GamePhase::ChoicePhase(_)));
+    }
+
+    #[test]
+    fn test_validate_nested_effects() {
+        let effects = vec![Effect::Sequence {
+            effects: vec![
+                Effect::Challenge(Challenge::default()),
+                Effect::Choice {
+                    options: Vec::new(),
+                },
+            ],
+        }];
+
+        assert_eq!(validate_effects(&effects, "Test", true).len(), 2);
+    }
+
+    #[test]
+    fn test_script_effect() {
+        let effect = Effect::Script {
+            script: "[gain_resource(\"Grain\", ship_area())]"
+                .to_owned(),
+        };
+
+        let gs = effect
+            .apply(
+                &GameState::init_state(),
+                &EffectContext::default(),
+            )
+            .unwrap();
+        assert_eq!(gs.resources.grain, 1);
+
+        let broken = Effect::Script {
+            script: "heal(".to_owned(),
+        };
+        assert_eq!(
+            validate_effects(&[broken], "Test", true).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_effects() {
+        let json = r#"[
+            { "type": "gainResource", "resource": "Coins", "amount": 1 },
+            { "type": "challenge", "skill": "Wits", "amount": 5,
+              "failure": [{ "type": "addFatigue", "amount": 1 }] },
+            { "type": "conditional",
+              "condition": { "type": "shipInArea", "area": 2 },
+              "then": [{ "type": "drawAbilityCards", "amount": 1 }] }
+        ]"#;
+
+        let effects: Vec<Effect> =
+            serde_json::from_str(json).unwrap();
+        assert_eq!(effects.len(), 3);
+    }
+}
@@ -1,81 +1,84 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::game_state::{challenge::Challenge, Skill};
+use super::{
+    challenge::Challenge,
+    effect::{
+        apply_effects, validate_effects, Effect, EffectContext,
+    },
+    GameState, Update,
+};
 
-use super::{GameState, Update};
-
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EventCard {
     pub name: String,
     pub options: Vec<EventOption>,
-    // TODO: deck index
     pub deck_index: u32,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct EventOption {
     pub text: String,
-    #[serde(skip_serializing)]
-    pub handle_option: fn(&GameState) -> Update,
+    #[serde(default)]
+    pub challenge: Option<Challenge>,
+    // Applied straight away when the option is chosen
+    #[serde(default, skip_serializing)]
+    pub effects: Vec<Effect>,
 }
 
-pub fn event_deck() -> Vec<EventCard> {
-    vec![
-        EventCard {
-            name: "Broken Biplane".to_owned(),
-            deck_index: 11,
-            options: vec![
-                EventOption {
-                    text: "Help repair the airplane (CRAFT 8)"
-                        .to_owned(),
-                    handle_option: (|g| {
-                        g.challenge(Challenge {
-                            skill: Skill::Craft,
-                            amount: 8,
-                            if_fail: |gs| {
-                                Ok(gs.clone())
-                                    .and_then(|g| take_damage(g, 5))
-                            },
-                            if_succeed: |gs| {
-                                Ok(gs.clone())
-                                    .and_then(gain_coin)
-                                    .and_then(gain_meat)
-                            },
-                        })
-                    }),
-                },
-                EventOption {
-                    text: "Ignore the plane".to_owned(),
-                    handle_option: |g| {
-                        Ok(g.clone()).and_then(|g| take_damage(g, 1))
-                    },
-                },
-            ],
-        },
-        // EventCard {
-        //     name: todo!(),
-        //     options: todo!(),
-        // },
-    ]
+impl EventOption {
+    pub fn choose(
+        &self,
+        state: &GameState,
+        ctx: &EffectContext,
+    ) -> Update {
+        let gs = apply_effects(&self.effects, state, ctx);
+
+        match &self.challenge {
+            Some(challenge) => {
+                gs.and_then(|g| g.challenge(challenge.clone()))
+            }
+            None => gs,
+        }
+    }
 }
 
-fn gain_coin(state: GameState) -> Update {
-    let mut gs = state.clone();
-    gs.resources.coins += 1;
+pub fn validate_event_cards(cards: &[EventCard]) -> Vec<String> {
+    let mut errors = Vec::new();
 
-    Ok(gs)
-}
+    for (ix, card) in cards.iter().enumerate() {
+        if card.name.trim().is_empty() {
+            errors.push(format!("Event card {} has no name", ix));
+        }
+        if card.options.is_empty() {
+            errors.push(format!(
+                "Event card {} has no options",
+                card.name
+            ));
+        }
+        if cards[..ix].iter().any(|c| c.deck_index == card.deck_index)
+        {
+            errors.push(format!(
+                "Event card {} reuses deck index {}",
+                card.name, card.deck_index
+            ));
+        }
 
-fn gain_meat(state: GameState) -> Update {
-    let mut gs = state.clone();
-    gs.resources.meat += 1;
+        for option in card.options.iter() {
+            let mut effects = option.effects.clone();
+            effects.extend(
+                option.challenge.clone().map(Effect::Challenge),
+            );
 
-    Ok(gs)
-}
+            errors.extend(validate_effects(
+                &effects,
+                &format!(
+                    "Option \"{}\" of {}",
+                    option.text, card.name
+                ),
+                true,
+            ));
+        }
+    }
 
-fn take_damage(state: GameState, damage: u8) -> Update {
-    // TODO: allow user to distribute damage
-    let mut gs = state.clone();
-    gs.crew[0].damage += damage;
-    Ok(gs)
+    errors
 }
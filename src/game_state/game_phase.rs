@@ -1,20 +1,53 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::{
-    challenge::Challenge, event_deck::EventCard, SearchToken,
+    challenge::{Challenge, ChallengeResult},
+    effect::ChoiceOption,
+    event_deck::EventCard,
+    SearchToken,
 };
 
 #[derive(Clone, Serialize)]
 pub enum GamePhase {
     ShipActionPhase(Option<ShipActionSubphase>),
     EventPhase(Option<EventCard>),
-    MainActionPhase(Vec<MainActionSubphase>),
+    MainActionPhase {
+        // Main actions chosen this turn, counted against the limit
+        taken: u32,
+        // The action being carried out, if any
+        current: Option<MainActionSubphase>,
+    },
     ChallengePhase {
         challenge: Challenge,
-        added: Option<u32>
+        // Set once the challenge is resolved
+        result: Option<ChallengeResult>,
+        // Added by ability cards and command tokens before the
+        // challenge is resolved
+        boost: u32,
+        // The crew members taking part
+        crew: Vec<usize>,
+        // The crew's skill plus the boost, so everyone can see where
+        // the challenge stands before it's resolved
+        total: u32,
+    },
+    ChoicePhase(Vec<ChoiceOption>),
+    // Damage waiting to be shared out between the crew, and the ship
+    // when it's allowed to take some
+    DamagePhase {
+        amount: u8,
+        ship: bool,
+    },
+    // After the main actions, until the turn is ended
+    EndTurnPhase,
+    // Nothing more can be done. The reason is a string table key.
+    GameOver {
+        reason: String,
     },
 }
 
+// The variant names are what clients see as the subphase, so they
+// keep the suffix
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Serialize, Default)]
 pub enum ShipActionSubphase {
     #[default]
@@ -22,10 +55,25 @@ pub enum ShipActionSubphase {
     DeckAction {
         search_tokens_drawn: Vec<SearchToken>,
     },
+    QuartersAction,
+    SickbayAction,
 }
 
-#[derive(Clone, Serialize, Default)]
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug)]
 pub enum MainActionSubphase {
-    #[default]
     Travel,
+    // Only where the ship is at a port
+    Port,
+    // Reading the storybook entry for the area, on a coast
+    Explore,
+    Rest,
+}
+
+impl GamePhase {
+    pub fn main_actions() -> Self {
+        GamePhase::MainActionPhase {
+            taken: 0,
+            current: None,
+        }
+    }
 }
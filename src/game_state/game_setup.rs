@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+// Options sent by the client when a new game is started
+#[derive(Deserialize, Default)]
+pub struct GameSetup {
+    // Which saved game to play. When not given, resuming uses the
+    // current game and a new game gets a fresh id.
+    #[serde(default)]
+    pub game_id: Option<String>,
+    #[serde(default)]
+    pub from_campaign: bool,
+    // Random if not given
+    #[serde(default)]
+    pub seed: Option<u64>,
+    // Content packs to play with on top of the base game. Ignored
+    // when resuming, the campaign keeps the packs it started with.
+    #[serde(default)]
+    pub packs: Vec<String>,
+    // Names of the crew on the ship, the pack's starting crew if
+    // not given. Ignored when resuming like the packs.
+    #[serde(default)]
+    pub crew: Vec<String>,
+}
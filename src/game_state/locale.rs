@@ -0,0 +1,263 @@
+use std::{collections::HashMap, fmt::Display, fs, iter, path::Path};
+
+use serde_json::Value;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+const LOCALES_DIR: &str = "locales";
+// Objects sent to clients keep their display text in this field
+const TEXT_FIELD: &str = "text";
+
+// Text shown to players is a key into the string tables, followed by
+// any arguments, e.g. "error.cant_play_now|ability.triage". The arguments
+// fill in {0}, {1}... and are looked up as keys too.
+pub fn message(key: &str, args: &[&dyn Display]) -> String {
+    iter::once(key.to_owned())
+        .chain(args.iter().map(|a| a.to_string()))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+// The string tables from every active pack, by locale. Each pack
+// keeps them in locales/<locale>.json.
+#[derive(Clone, Default)]
+pub struct Locales {
+    tables: HashMap<String, HashMap<String, String>>,
+}
+
+impl Locales {
+    // Later packs add keys and can replace the text of earlier ones
+    pub fn read_pack(&mut self, pack_dir: &Path) -> Vec<String> {
+        let dir = pack_dir.join(LOCALES_DIR);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+
+        let mut errors = Vec::new();
+        for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+            if path.extension() != Some("json".as_ref()) {
+                continue;
+            }
+            let Some(locale) =
+                path.file_stem().and_then(|s| s.to_str())
+            else {
+                continue;
+            };
+
+            let table = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<HashMap<String, String>>(
+                        &json,
+                    )
+                    .map_err(|e| e.to_string())
+                });
+            match table {
+                Ok(table) => self
+                    .tables
+                    .entry(locale.to_owned())
+                    .or_default()
+                    .extend(table),
+                Err(err) => errors.push(format!(
+                    "{}: {}",
+                    path.display(),
+                    err
+                )),
+            }
+        }
+
+        errors
+    }
+
+    pub fn has_locale(&self, locale: &str) -> bool {
+        self.tables.contains_key(locale)
+    }
+
+    // Keys that the default locale has no text for
+    pub fn missing_keys<'a>(
+        &self,
+        keys: impl Iterator<Item = &'a str>,
+    ) -> Vec<String> {
+        keys.filter(|key| self.lookup(DEFAULT_LOCALE, key).is_none())
+            .map(|key| {
+                format!("No {} text for {}", DEFAULT_LOCALE, key)
+            })
+            .collect()
+    }
+
+    // Every key with its text, falling back to the default locale.
+    // Clients look up names and other game data sent as keys here.
+    pub fn strings(&self, locale: &str) -> HashMap<String, String> {
+        [DEFAULT_LOCALE, locale]
+            .iter()
+            .filter_map(|l| self.tables.get(*l))
+            .flat_map(|table| table.clone())
+            .collect()
+    }
+
+    // Falls back to the default locale, then to the key itself
+    fn lookup(&self, locale: &str, key: &str) -> Option<&str> {
+        [locale, DEFAULT_LOCALE]
+            .iter()
+            .filter_map(|l| self.tables.get(*l))
+            .find_map(|table| table.get(key))
+            .map(String::as_str)
+    }
+
+    pub fn render(&self, locale: &str, text: &str) -> String {
+        let mut parts = text.split('|');
+        let key = parts.next().unwrap_or_default();
+        let template = self.lookup(locale, key).unwrap_or(key);
+
+        parts.enumerate().fold(
+            template.to_owned(),
+            |out, (ix, arg)| {
+                let arg = self.lookup(locale, arg).unwrap_or(arg);
+                out.replace(&format!("{{{}}}", ix), arg)
+            },
+        )
+    }
+
+    // Renders the display text in the value: a bare string, like an
+    // error, or any `text` field. Everything else is game data that
+    // clients may use as identifiers, like card names, and look up
+    // in the strings they're sent.
+    pub fn localize_json(&self, locale: &str, value: &mut Value) {
+        match value {
+            Value::String(text) => self.localize_text(locale, text),
+            _ => self.localize_fields(locale, value),
+        }
+    }
+
+    // Text that isn't a message, like a store error, is left alone
+    fn localize_text(&self, locale: &str, text: &mut String) {
+        let key = text.split('|').next().unwrap_or_default();
+        if self.lookup(locale, key).is_some() {
+            *text = self.render(locale, text);
+        }
+    }
+
+    fn localize_fields(&self, locale: &str, value: &mut Value) {
+        match value {
+            Value::Array(values) => values
+                .iter_mut()
+                .for_each(|v| self.localize_fields(locale, v)),
+            Value::Object(map) => {
+                for (field, v) in map.iter_mut() {
+                    match v {
+                        Value::String(text)
+                            if field == TEXT_FIELD =>
+                        {
+                            self.localize_text(locale, text)
+                        }
+                        _ => self.localize_fields(locale, v),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::game_state::content::{Content, DEFAULT_CONTENT_DIR};
+
+    fn locales() -> Locales {
+        Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap().locales
+    }
+
+    #[test]
+    fn test_render_with_args() {
+        let text = message(
+            "error.cant_play_now",
+            &[&"ability.focused_mind"],
+        );
+
+        assert_eq!(
+            locales().render("en", &text),
+            "Focused Mind can't be played now"
+        );
+        assert_eq!(
+            locales().render("de", &text),
+            "Fokussierter Geist kann jetzt nicht gespielt werden"
+        );
+    }
+
+    #[test]
+    fn test_fall_back_to_default_locale() {
+        let mut locales = locales();
+        locales
+            .tables
+            .get_mut("pt")
+            .unwrap()
+            .remove("error.wrong_phase");
+
+        assert_eq!(
+            locales.render("pt", "error.wrong_phase"),
+            locales.render("en", "error.wrong_phase")
+        );
+        assert_eq!(
+            locales.render("pt", "Sofi Odessa"),
+            "Sofi Odessa"
+        );
+    }
+
+    #[test]
+    fn test_localize_json() {
+        let mut value = json!({
+            "name": "event.broken_biplane",
+            "options": [{ "text": "event.broken_biplane.ignore" }],
+            "skill": "Craft",
+        });
+
+        locales().localize_json("pt", &mut value);
+        assert_eq!(value["options"][0]["text"], "Ignorar o avião");
+        assert_eq!(value["name"], "event.broken_biplane");
+        assert_eq!(value["skill"], "Craft");
+
+        let mut error = json!("error.no_crew");
+        locales().localize_json("en", &mut error);
+        assert_eq!(error, "Crew member does not exist");
+    }
+
+    #[test]
+    fn test_strings_for_names() {
+        let mut locales = locales();
+        locales
+            .tables
+            .get_mut("de")
+            .unwrap()
+            .remove("ability.triage");
+        let strings = locales.strings("de");
+
+        assert_eq!(
+            strings["ability.focused_mind"],
+            "Fokussierter Geist"
+        );
+        assert_eq!(strings["ability.triage"], "Triage");
+    }
+
+    #[test]
+    fn test_locales_have_the_same_keys() {
+        let locales = locales();
+        let default = &locales.tables[DEFAULT_LOCALE];
+
+        for (locale, table) in locales.tables.iter() {
+            let mut missing: Vec<&String> = default
+                .keys()
+                .filter(|k| !table.contains_key(*k))
+                .collect();
+            missing.sort();
+            assert!(
+                missing.is_empty(),
+                "{} lacks {:?}",
+                locale,
+                missing
+            );
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use iter_tools::{prelude, Itertools};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone)]
 pub struct GameMap {
@@ -10,16 +10,25 @@ pub struct GameMap {
 }
 
 impl GameMap {
+    // The ship starts in the start area
+    pub fn new(map_data: &MapData) -> Self {
+        GameMap {
+            ship_area: START_AREA,
+            map_data: map_data.clone(),
+        }
+    }
+
+    pub fn current_area(&self) -> &Area {
+        // We want to panic here, our graph is incomplete
+        self.map_data.areas.get(&self.ship_area).unwrap()
+    }
+
     fn current_region(&self) -> RegionIx {
-        self.map_data
-            .areas
-            .get(&self.ship_area)
-            .map(|a| a.region)
-            .unwrap() // We want to panic here, our graph is incomplete
+        self.current_area().region
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct MapData {
     areas: HashMap<AreaIx, Area>,
     area_graph: HashMap<AreaIx, Vec<AreaIx>>,
@@ -31,35 +40,210 @@ impl MapData {
             .iter()
             .filter(|(_, area)| area.region == region)
             .map(|(ix, _)| *ix)
+            .sorted()
             .collect()
     }
 
+    pub fn ports(&self) -> impl Iterator<Item = &Port> {
+        self.areas.values().flat_map(|a| a.ports.iter())
+    }
+
+    pub fn has_area(&self, area: AreaIx) -> bool {
+        self.areas.contains_key(&area)
+    }
+
+    // Unknown areas aren't adjacent to anything
+    pub fn is_adjacent(&self, from: AreaIx, to: AreaIx) -> bool {
+        self.area_graph
+            .get(&from)
+            .is_some_and(|adjacent| adjacent.contains(&to))
+    }
+
     fn adjacent_areas(&self, area: AreaIx) -> Vec<AreaIx> {
         self.area_graph[&area].clone()
     }
+
+    // Adds the areas from a content pack. Edges are kept as written,
+    // so validate catches a one-way edge, except that an edge to an
+    // earlier pack's area is added both ways as that pack can't link
+    // back to it.
+    pub fn add_areas(&mut self, areas: &[AreaDef]) -> Vec<String> {
+        let mut errors = Vec::new();
+        let earlier: HashSet<AreaIx> =
+            self.areas.keys().copied().collect();
+
+        for def in areas {
+            if self.areas.contains_key(&def.id) {
+                errors.push(format!(
+                    "Area {} is defined twice",
+                    def.id
+                ));
+                continue;
+            }
+
+            self.areas.insert(def.id, def.area.clone());
+            self.area_graph.entry(def.id).or_default();
+            for other in def.adjacent.iter() {
+                self.link(def.id, *other);
+                if earlier.contains(other) {
+                    self.link(*other, def.id);
+                }
+            }
+        }
+
+        errors
+    }
+
+    fn link(&mut self, from: AreaIx, to: AreaIx) {
+        let adjacent = self.area_graph.entry(from).or_default();
+        if !adjacent.contains(&to) {
+            adjacent.push(to);
+        }
+    }
+
+    // The map code assumes every area has edges and every edge goes
+    // both ways, so check that up front
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        let mut port_ids = HashSet::new();
+        for (ix, area) in
+            self.areas.iter().sorted_by_key(|(ix, _)| **ix)
+        {
+            if !self.area_graph.contains_key(ix) {
+                errors.push(format!("Area {} has no edges", ix));
+            }
+            if area.terrain == Terrain::Sea && !area.ports.is_empty()
+            {
+                errors.push(format!(
+                    "Area {} is open sea but has a port",
+                    ix
+                ));
+            }
+            for port in area.ports.iter() {
+                if !port_ids.insert(port.id) {
+                    errors.push(format!(
+                        "Port {} is repeated",
+                        port.id
+                    ));
+                }
+            }
+        }
+
+        for (area, adjacent) in self.area_graph.iter().sorted() {
+            if !self.areas.contains_key(area) {
+                errors.push(format!(
+                    "Area graph has unknown area {}",
+                    area
+                ));
+            }
+            for other in adjacent {
+                if !self.areas.contains_key(other) {
+                    errors.push(format!(
+                        "Area {} links to unknown area {}",
+                        area, other
+                    ));
+                } else if !self
+                    .area_graph
+                    .get(other)
+                    .is_some_and(|a| a.contains(area))
+                {
+                    errors.push(format!(
+                        "Area {} links to {} but not back",
+                        area, other
+                    ));
+                }
+            }
+        }
+
+        if !self.areas.contains_key(&START_AREA) {
+            errors.push(format!(
+                "Start area {} does not exist",
+                START_AREA
+            ));
+            return errors;
+        }
+
+        let mut reached = HashSet::from([START_AREA]);
+        let mut queue = vec![START_AREA];
+        while let Some(area) = queue.pop() {
+            for other in
+                self.area_graph.get(&area).into_iter().flatten()
+            {
+                if reached.insert(*other) {
+                    queue.push(*other);
+                }
+            }
+        }
+        for area in self.areas.keys().sorted() {
+            if !reached.contains(area) {
+                errors.push(format!(
+                    "Area {} can't be reached from area {}",
+                    area, START_AREA
+                ));
+            }
+        }
+
+        errors
+    }
 }
 
-#[derive(Clone)]
-struct Area {
-    region: RegionIx,
+#[derive(
+    Clone, Copy, Serialize, Deserialize, PartialEq, Debug, Default,
+)]
+pub enum Terrain {
+    #[default]
+    Sea,
+    Coast,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Area {
+    pub region: RegionIx,
+    #[serde(default)]
+    pub terrain: Terrain,
+    // The atlas page the area is drawn on
+    pub page: u32,
+    // Only coastal areas have ports
+    #[serde(default)]
+    pub ports: Vec<Port>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Port {
+    pub id: PortIx,
+    // String table key
+    pub name: String,
+    // The storybook entry read on arriving
+    pub page: u32,
 }
-// #[derive(Clone)]
-// pub struct Area {
-//     ports: Vec<PortIx>
-// }
 
-pub struct Port {}
+// An area as written in a pack's areas.json
+#[derive(Deserialize)]
+pub struct AreaDef {
+    id: AreaIx,
+    #[serde(flatten)]
+    area: Area,
+    #[serde(default)]
+    adjacent: Vec<AreaIx>,
+}
 
 type RegionIx = u32;
 type AreaIx = u32;
 type PortIx = u32;
 
+// Where a new game puts the ship
+pub const START_AREA: AreaIx = 1;
+
 #[derive(Clone, Serialize)]
 pub struct SerialMap {
     pub ship_area: AreaIx,
     pub adjacent_areas: Vec<AreaIx>,
     pub visible_areas: Vec<AreaIx>,
     pub current_region: RegionIx,
+    pub terrain: Terrain,
+    pub page: u32,
+    pub ports: Vec<Port>,
 }
 
 impl From<GameMap> for SerialMap {
@@ -67,7 +251,8 @@ impl From<GameMap> for SerialMap {
         let adj = map.map_data.adjacent_areas(map.ship_area);
 
         // The visible areas are all the areas in the region plus areas immediately adjacent
-        let areas = map.map_data.areas_in_region(map.current_region());
+        let areas =
+            map.map_data.areas_in_region(map.current_region());
 
         let all_adjacent: Vec<AreaIx> = areas
             .iter()
@@ -81,36 +266,133 @@ impl From<GameMap> for SerialMap {
             .cloned()
             .collect();
 
+        let area = map.current_area();
         SerialMap {
             ship_area: map.ship_area,
             adjacent_areas: adj.clone(),
             visible_areas,
             current_region: map.current_region(),
+            terrain: area.terrain,
+            page: area.page,
+            ports: area.ports.clone(),
         }
     }
 }
 
-// map data
-impl Default for MapData {
-    fn default() -> Self {
-        let mut areas = HashMap::new();
-        areas.insert(1, Area { region: 1 });
-        areas.insert(2, Area { region: 1 });
-        areas.insert(3, Area { region: 1 });
-        areas.insert(4, Area { region: 1 });
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn area_def(
+        id: AreaIx,
+        region: RegionIx,
+        adjacent: &[AreaIx],
+    ) -> AreaDef {
+        AreaDef {
+            id,
+            area: Area {
+                region,
+                terrain: Terrain::Sea,
+                page: 1,
+                ports: Vec::new(),
+            },
+            adjacent: adjacent.to_vec(),
+        }
+    }
+
+    // Two regions, joined between areas 1 and 6
+    fn test_map() -> MapData {
+        let mut map = MapData::default();
+        let errors = map.add_areas(&[
+            area_def(1, 1, &[2, 4, 6]),
+            area_def(2, 1, &[1, 3]),
+            area_def(3, 1, &[2, 4]),
+            area_def(4, 1, &[1, 3]),
+            area_def(6, 2, &[1, 7]),
+            area_def(7, 2, &[6]),
+        ]);
+        assert!(errors.is_empty());
+        map
+    }
+
+    #[test]
+    fn test_map_is_valid() {
+        assert!(test_map().validate().is_empty());
+    }
+
+    #[test]
+    fn test_one_way_edge() {
+        let mut map = test_map();
+        map.area_graph.get_mut(&7).unwrap().clear();
+
+        assert_eq!(
+            map.validate(),
+            vec!["Area 6 links to 7 but not back"]
+        );
+    }
 
-        areas.insert(6, Area { region: 2 });
-        areas.insert(7, Area { region: 2 });
+    #[test]
+    fn test_edges_kept_as_written() {
+        let mut map = MapData::default();
+        map.add_areas(&[area_def(1, 1, &[2]), area_def(2, 1, &[])]);
 
-        let mut area_graph = HashMap::new();
+        assert_eq!(
+            map.validate(),
+            vec!["Area 1 links to 2 but not back"]
+        );
+    }
+
+    #[test]
+    fn test_add_areas() {
+        let mut map = test_map();
+        let errors = map
+            .add_areas(&[area_def(8, 2, &[7]), area_def(1, 1, &[])]);
+
+        assert_eq!(errors, vec!["Area 1 is defined twice"]);
+        assert_eq!(map.adjacent_areas(7), vec![6, 8]);
+        assert!(map.validate().is_empty());
+    }
 
-        area_graph.insert(1, vec![2, 4, 6]);
-        area_graph.insert(2, vec![1, 3]);
-        area_graph.insert(3, vec![2, 4]);
-        area_graph.insert(4, vec![3, 1]);
-        area_graph.insert(6, vec![1, 7]);
-        area_graph.insert(7, vec![6]);
+    #[test]
+    fn test_unknown_area() {
+        let mut map = test_map();
+        map.area_graph.get_mut(&7).unwrap().push(9);
+
+        assert_eq!(
+            map.validate(),
+            vec!["Area 7 links to unknown area 9"]
+        );
+    }
+
+    #[test]
+    fn test_is_adjacent() {
+        let map = test_map();
+
+        assert!(map.is_adjacent(1, 6));
+        assert!(map.is_adjacent(6, 1));
+        assert!(!map.is_adjacent(1, 3));
+        assert!(!map.is_adjacent(9, 1));
+    }
+
+    #[test]
+    fn test_ports() {
+        let mut map = test_map();
+        let port = Port {
+            id: 1,
+            name: "port.test".to_owned(),
+            page: 10,
+        };
+        for ix in [2, 6] {
+            map.areas.get_mut(&ix).unwrap().ports.push(port.clone());
+        }
+        map.areas.get_mut(&6).unwrap().terrain = Terrain::Coast;
 
-        Self { areas, area_graph }
+        assert_eq!(
+            map.validate(),
+            vec![
+                "Area 2 is open sea but has a port",
+                "Port 1 is repeated"
+            ]
+        );
     }
 }
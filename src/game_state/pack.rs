@@ -0,0 +1,180 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::locale::message;
+
+// The base game is always played, expansions are added on top
+pub const BASE_PACK: &str = "base";
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+// Read from manifest.json in the pack's directory. The id has to
+// match the directory name.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Manifest {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+// What a save records about each pack the campaign was played with
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct PackRef {
+    pub id: String,
+    pub version: String,
+}
+
+impl From<&Manifest> for PackRef {
+    fn from(manifest: &Manifest) -> Self {
+        PackRef {
+            id: manifest.id.clone(),
+            version: manifest.version.clone(),
+        }
+    }
+}
+
+// Every directory in the content directory with a manifest, by id
+pub fn available_packs(dir: &str) -> Result<Vec<Manifest>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Could not read {}: {}", dir, e))?;
+
+    let mut packs = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path().join(MANIFEST_FILE);
+        if !path.is_file() {
+            continue;
+        }
+
+        let manifest = read_manifest(&path)?;
+        if entry.file_name().to_str() != Some(manifest.id.as_str()) {
+            return Err(format!(
+                "{}: id {} doesn't match the directory",
+                path.display(),
+                manifest.id
+            ));
+        }
+        packs.push(manifest);
+    }
+
+    packs.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(packs)
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest, String> {
+    fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            serde_json::from_str(&json).map_err(|e| e.to_string())
+        })
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Orders the packs so each one comes after its dependencies, adding
+// any dependency that wasn't asked for
+pub fn resolve_packs(
+    available: &[Manifest],
+    ids: &[String],
+) -> Result<Vec<Manifest>, String> {
+    let mut resolved = Vec::new();
+    for id in ids {
+        visit(available, id, &mut Vec::new(), &mut resolved)?;
+    }
+
+    Ok(resolved)
+}
+
+fn visit(
+    available: &[Manifest],
+    id: &str,
+    path: &mut Vec<String>,
+    resolved: &mut Vec<Manifest>,
+) -> Result<(), String> {
+    if resolved.iter().any(|m| m.id == id) {
+        return Ok(());
+    }
+    if path.iter().any(|p| p == id) {
+        return Err(format!(
+            "Packs depend on each other: {} -> {}",
+            path.join(" -> "),
+            id
+        ));
+    }
+
+    let manifest =
+        available.iter().find(|m| m.id == id).ok_or_else(|| {
+            match path.last() {
+                Some(parent) => {
+                    format!(
+                        "{} needs the missing pack {}",
+                        parent, id
+                    )
+                }
+                None => message("error.unknown_pack", &[&id]),
+            }
+        })?;
+
+    path.push(id.to_owned());
+    for dependency in manifest.dependencies.iter() {
+        visit(available, dependency, path, resolved)?;
+    }
+    path.pop();
+
+    resolved.push(manifest.clone());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn manifest(id: &str, dependencies: &[&str]) -> Manifest {
+        Manifest {
+            id: id.to_owned(),
+            version: "1.0.0".to_owned(),
+            dependencies: dependencies
+                .iter()
+                .map(|d| d.to_string())
+                .collect(),
+        }
+    }
+
+    fn ids(packs: &[Manifest]) -> Vec<&str> {
+        packs.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_dependencies_come_first() {
+        let available = vec![
+            manifest("base", &[]),
+            manifest("islands", &["base"]),
+            manifest("monsters", &["islands"]),
+        ];
+
+        let packs = resolve_packs(
+            &available,
+            &["monsters".to_owned(), "base".to_owned()],
+        )
+        .unwrap();
+        assert_eq!(ids(&packs), vec!["base", "islands", "monsters"]);
+    }
+
+    #[test]
+    fn test_missing_dependency() {
+        let available = vec![manifest("islands", &["base"])];
+
+        assert_eq!(
+            resolve_packs(&available, &["islands".to_owned()]),
+            Err("islands needs the missing pack base".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        let available =
+            vec![manifest("a", &["b"]), manifest("b", &["a"])];
+
+        assert!(resolve_packs(&available, &["a".to_owned()]).is_err());
+    }
+}
@@ -1,8 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::AbilityCard;
 
-#[derive(Default, Serialize, Clone)]
+#[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Player {
     pub command_tokens: u32,
     pub hand: Vec<AbilityCard>,
@@ -19,8 +19,7 @@ impl Player {
     ) -> Result<(Player, AbilityCard), String> {
         let mut player = self.clone();
         if player.hand.len() <= card_ix {
-            Err("this card does not exist in the players hand"
-                .to_owned())
+            Err("error.no_card".to_owned())
         } else {
             let card = player.hand.remove(card_ix);
             Ok((player, card))
@@ -0,0 +1,209 @@
+use serde_json::{json, Value};
+
+use super::{
+    campaign::Campaign, locale::message, pack::BASE_PACK, ShipRoom,
+};
+
+// Bump this whenever the saved types change, and add a migration
+// from the previous version to MIGRATIONS
+pub const SAVE_VERSION: u32 = 5;
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+// MIGRATIONS[n] upgrades a save from version n + 1 to n + 2
+const MIGRATIONS: [Migration; (SAVE_VERSION - 1) as usize] =
+    [v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5];
+
+pub fn write_save(campaign: &Campaign) -> Result<Value, String> {
+    serde_json::to_value(campaign)
+        .map(|campaign| {
+            json!({
+                "version": SAVE_VERSION,
+                "campaign": campaign,
+            })
+        })
+        .map_err(|e| e.to_string())
+}
+
+pub fn read_save(save: Value) -> Result<Campaign, String> {
+    let version = save_version(&save)?;
+
+    if version == 0 || version > SAVE_VERSION {
+        return Err(message("error.save_version", &[&version]));
+    }
+
+    MIGRATIONS[(version - 1) as usize..]
+        .iter()
+        .try_fold(save, |save, migration| migration(save))
+        .and_then(|save| {
+            serde_json::from_value(save["campaign"].clone())
+                .map_err(|e| message("error.bad_save_detail", &[&e]))
+        })
+        .and_then(|campaign: Campaign| {
+            // Turns go round the players, so there has to be one
+            if campaign.players.is_empty() {
+                Err("error.save_without_players".to_owned())
+            } else {
+                Ok(campaign)
+            }
+        })
+}
+
+fn save_version(save: &Value) -> Result<u32, String> {
+    match save.get("version") {
+        // Saves from before versioning were the bare campaign
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or("error.bad_save".to_owned()),
+    }
+}
+
+// Version 2 wraps the campaign and stamps it with the version
+fn v1_to_v2(save: Value) -> Result<Value, String> {
+    Ok(json!({
+        "version": 2,
+        "campaign": save,
+    }))
+}
+
+// Version 3 records the content packs, older saves only had the
+// base game. Which version of it isn't known, so it's left empty
+// and the installed one is used, see Content::check_pack_versions.
+fn v2_to_v3(mut save: Value) -> Result<Value, String> {
+    save["version"] = json!(3);
+    save["campaign"]["packs"] =
+        json!([{ "id": BASE_PACK, "version": "" }]);
+    Ok(save)
+}
+
+// Version 4 adds the adventure cards, the ship's damage and where
+// the game is in the round. Some version 3 saves already have them,
+// the others start over from the first turn.
+fn v3_to_v4(mut save: Value) -> Result<Value, String> {
+    save["version"] = json!(4);
+    let campaign = save["campaign"]
+        .as_object_mut()
+        .ok_or("error.bad_save".to_owned())?;
+
+    for (field, value) in [
+        ("adventure_cards", json!([])),
+        ("ship_damage", json!(0)),
+        ("turn", json!(1)),
+        ("round", json!(1)),
+        ("active_player", json!(0)),
+        ("previous_room", json!(ShipRoom::None)),
+    ] {
+        campaign.entry(field).or_insert(value);
+    }
+    Ok(save)
+}
+
+// Version 5 keeps the seed, which older saves didn't record
+fn v4_to_v5(mut save: Value) -> Result<Value, String> {
+    save["version"] = json!(5);
+    save["campaign"]["seed"] = Value::Null;
+    Ok(save)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    use crate::game_state::GameState;
+
+    #[test_case(include_str!("save_samples/v1.json"); "version 1")]
+    #[test_case(include_str!("save_samples/v2.json"); "version 2")]
+    #[test_case(include_str!("save_samples/v3.json"); "version 3")]
+    #[test_case(include_str!("save_samples/v4.json"); "version 4")]
+    #[test_case(include_str!("save_samples/v5.json"); "version 5")]
+    fn test_read_sample_save(sample: &str) {
+        let campaign =
+            read_save(serde_json::from_str(sample).unwrap()).unwrap();
+
+        assert_eq!(campaign.crew.len(), 2);
+        assert_eq!(campaign.crew[0].name, "Sofi Odessa");
+        assert_eq!(campaign.crew[0].damage, 2);
+        assert_eq!(campaign.crew[0].fatigue, 1);
+        assert_eq!(campaign.resources.coins, 3);
+        assert_eq!(campaign.resources.meat, 2);
+        assert_eq!(campaign.players[0].command_tokens, 4);
+        assert_eq!(campaign.players[0].hand[0].name, "Counsel");
+        assert_eq!(campaign.ship_area, 6);
+        assert_eq!(campaign.pack_ids(), vec![BASE_PACK]);
+    }
+
+    #[test]
+    fn test_turn_kept_from_version_4() {
+        let sample = include_str!("save_samples/v4.json");
+        let campaign =
+            read_save(serde_json::from_str(sample).unwrap()).unwrap();
+
+        assert_eq!(
+            campaign.adventure_cards[0].name,
+            "adventure.lantern"
+        );
+        assert_eq!(campaign.ship_damage, 1);
+        assert_eq!((campaign.turn, campaign.round), (5, 3));
+        assert!(matches!(campaign.previous_room, ShipRoom::Galley));
+    }
+
+    #[test]
+    fn test_seed_from_version_5() {
+        let read = |sample: &str| {
+            read_save(serde_json::from_str(sample).unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            read(include_str!("save_samples/v5.json")).seed,
+            Some(17)
+        );
+        assert_eq!(
+            read(include_str!("save_samples/v4.json")).seed,
+            None
+        );
+    }
+
+    #[test]
+    fn test_turn_starts_over_from_version_3() {
+        let sample = include_str!("save_samples/v3.json");
+        let campaign =
+            read_save(serde_json::from_str(sample).unwrap()).unwrap();
+
+        assert!(campaign.adventure_cards.is_empty());
+        assert_eq!((campaign.turn, campaign.round), (1, 1));
+        assert!(matches!(campaign.previous_room, ShipRoom::None));
+    }
+
+    #[test]
+    fn test_err_if_no_players() {
+        let mut save: Value = serde_json::from_str(include_str!(
+            "save_samples/v4.json"
+        ))
+        .unwrap();
+        save["campaign"]["players"] = json!([]);
+
+        assert_eq!(
+            read_save(save).err(),
+            Some("error.save_without_players".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let campaign = GameState::init_state().end_session().unwrap();
+        let save = write_save(&campaign).unwrap();
+
+        assert_eq!(save["version"], SAVE_VERSION);
+        assert!(read_save(save).is_ok());
+    }
+
+    #[test_case(json!({ "version": 0, "campaign": {} }); "version 0")]
+    #[test_case(json!({ "version": SAVE_VERSION + 1 }); "newer version")]
+    #[test_case(json!({ "version": "2" }); "not a number")]
+    fn test_err_if_unsupported_version(save: Value) {
+        assert!(read_save(save).is_err());
+    }
+}
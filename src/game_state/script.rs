@@ -0,0 +1,463 @@
+use std::rc::Rc;
+
+use rhai::{
+    packages::{
+        BasicArrayPackage, BasicMapPackage, BasicMathPackage,
+        CorePackage, LogicPackage, MoreStringPackage, Package,
+    },
+    Array, Dynamic, Engine, EvalAltResult,
+};
+use serde_json::Value;
+
+use super::{
+    challenge::Challenge,
+    crew::Crew,
+    effect::{ChoiceOption, Effect, EffectContext},
+    game_phase::GamePhase,
+    skill::Skill,
+    GameState, Resource,
+};
+
+// Card scripts can't touch the state directly. They read from a copy
+// of it and return the effects to apply, so a failing script leaves
+// the game as it was.
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_ARRAY_SIZE: usize = 1_000;
+const MAX_MAP_SIZE: usize = 1_000;
+const MAX_STRING_SIZE: usize = 10_000;
+
+// The packages exclude anything that reads the clock or the
+// filesystem so a script always gives the same result
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new_raw();
+
+    engine.register_global_module(
+        CorePackage::new().as_shared_module(),
+    );
+    engine.register_global_module(
+        LogicPackage::new().as_shared_module(),
+    );
+    engine.register_global_module(
+        BasicMathPackage::new().as_shared_module(),
+    );
+    engine.register_global_module(
+        BasicArrayPackage::new().as_shared_module(),
+    );
+    engine.register_global_module(
+        BasicMapPackage::new().as_shared_module(),
+    );
+    engine.register_global_module(
+        MoreStringPackage::new().as_shared_module(),
+    );
+
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_array_size(MAX_ARRAY_SIZE);
+    engine.set_max_map_size(MAX_MAP_SIZE);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.disable_symbol("eval");
+
+    register_effects(&mut engine);
+    engine
+}
+
+fn register_effects(engine: &mut Engine) {
+    engine.register_type_with_name::<Effect>("Effect");
+    engine.register_type_with_name::<ChoiceOption>("ChoiceOption");
+
+    engine.register_fn(
+        "gain_resource",
+        |name: &str, amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::GainResource {
+                resource: resource(name)?,
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "lose_resource",
+        |name: &str, amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::LoseResource {
+                resource: resource(name)?,
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "take_damage",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::TakeDamage {
+                amount: number(amount)?,
+                ship: false,
+            })
+        },
+    );
+    engine.register_fn(
+        "take_damage",
+        |amount: i64, ship: bool| -> ScriptResult<Effect> {
+            Ok(Effect::TakeDamage {
+                amount: number(amount)?,
+                ship,
+            })
+        },
+    );
+    engine.register_fn(
+        "heal",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::Heal {
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "add_fatigue",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::AddFatigue {
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "remove_fatigue",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::RemoveFatigue {
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "draw_ability_cards",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::DrawAbilityCards {
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "give_command_tokens",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::GiveCommandTokens {
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "boost_challenge",
+        |amount: i64| -> ScriptResult<Effect> {
+            Ok(Effect::BoostChallenge {
+                amount: number(amount)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "move_ship",
+        |area: i64| -> ScriptResult<Effect> {
+            Ok(Effect::MoveShip {
+                to_area: number(area)?,
+            })
+        },
+    );
+    engine.register_fn("challenge", |skill: &str, amount: i64| {
+        challenge(skill, amount, Array::new(), Array::new())
+    });
+    engine.register_fn("challenge", challenge);
+    engine.register_fn(
+        "choice_option",
+        |text: &str, effects: Array| -> ScriptResult<ChoiceOption> {
+            Ok(ChoiceOption {
+                text: text.to_owned(),
+                effects: effects_from_array(effects)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "choice",
+        |options: Array| -> ScriptResult<Effect> {
+            let options = options
+                .into_iter()
+                .map(|o| {
+                    o.try_cast::<ChoiceOption>()
+                        .ok_or("choice takes a list of choice_option")
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Effect::Choice { options })
+        },
+    );
+}
+
+// Read only access to the state the script runs against
+fn register_queries(
+    engine: &mut Engine,
+    state: &GameState,
+    ctx: &EffectContext,
+) {
+    let gs = Rc::new(state.clone());
+
+    let g = gs.clone();
+    engine.register_fn(
+        "resource",
+        move |name: &str| -> ScriptResult<i64> {
+            Ok(i64::from(g.resources.get(resource(name)?)))
+        },
+    );
+    let g = gs.clone();
+    engine
+        .register_fn("ship_area", move || i64::from(g.map.ship_area));
+    let g = gs.clone();
+    engine.register_fn("crew_count", move || g.crew.len() as i64);
+    let g = gs.clone();
+    engine.register_fn("crew_damage", move |ix: i64| {
+        crew_stat(&g, ix, |c| c.damage)
+    });
+    let g = gs.clone();
+    engine.register_fn("crew_fatigue", move |ix: i64| {
+        crew_stat(&g, ix, |c| c.fatigue)
+    });
+    let g = gs.clone();
+    let player_ix = ctx.player_ix;
+    engine.register_fn("hand_size", move || {
+        g.players.get(player_ix).map_or(0, |p| p.hand.len() as i64)
+    });
+    let crew_ix = ctx.crew_ix;
+    engine.register_fn("target_crew", move || match crew_ix {
+        Some(ix) => Dynamic::from(ix as i64),
+        None => Dynamic::UNIT,
+    });
+    let g = gs.clone();
+    engine.register_fn(
+        "deck_size",
+        move |name: &str| -> ScriptResult<i64> {
+            let size = match name {
+                "ability" => g.ability_deck.remaining(),
+                "adventure" => g.adventure_deck.remaining(),
+                "event" => g.event_card_deck.remaining(),
+                "fate" => g.fate_deck.remaining(),
+                "searchToken" => g.search_token_deck.remaining(),
+                _ => {
+                    return Err(
+                        format!("Unknown deck {}", name).into()
+                    )
+                }
+            };
+            Ok(size as i64)
+        },
+    );
+    engine.register_fn("phase", move || phase_name(&gs.phase()));
+}
+
+// The name clients see for the phase, e.g. "ChallengePhase"
+fn phase_name(phase: &GamePhase) -> String {
+    match serde_json::to_value(phase) {
+        Ok(Value::String(name)) => name,
+        Ok(Value::Object(fields)) => {
+            fields.keys().next().cloned().unwrap_or_default()
+        }
+        _ => String::new(),
+    }
+}
+
+fn challenge(
+    skill: &str,
+    amount: i64,
+    success: Array,
+    failure: Array,
+) -> ScriptResult<Effect> {
+    Ok(Effect::Challenge(Challenge {
+        skill: skill.parse::<Skill>()?,
+        amount: number(amount)?,
+        success: effects_from_array(success)?,
+        failure: effects_from_array(failure)?,
+    }))
+}
+
+fn crew_stat(
+    state: &GameState,
+    ix: i64,
+    stat: impl Fn(&Crew) -> u8,
+) -> ScriptResult<i64> {
+    usize::try_from(ix)
+        .ok()
+        .and_then(|ix| state.crew.get(ix))
+        .map(|c| i64::from(stat(c)))
+        .ok_or(format!("No crew member {}", ix).into())
+}
+
+fn resource(name: &str) -> ScriptResult<Resource> {
+    serde_json::from_value(Value::String(name.to_owned()))
+        .map_err(|_| format!("Unknown resource {}", name).into())
+}
+
+fn number<T: TryFrom<i64>>(n: i64) -> ScriptResult<T> {
+    T::try_from(n)
+        .map_err(|_| format!("{} is out of range", n).into())
+}
+
+fn effects_from_array(array: Array) -> ScriptResult<Vec<Effect>> {
+    array
+        .into_iter()
+        .map(|e| {
+            e.try_cast::<Effect>()
+                .ok_or("expected a list of effects".into())
+        })
+        .collect()
+}
+
+// A script ends with an effect, a list of effects or nothing
+fn effects_from_result(result: Dynamic) -> ScriptResult<Vec<Effect>> {
+    if result.is_unit() {
+        Ok(Vec::new())
+    } else if result.is::<Effect>() {
+        Ok(vec![result.cast::<Effect>()])
+    } else if result.is_array() {
+        effects_from_array(result.cast::<Array>())
+    } else {
+        Err(format!(
+            "a script must return effects, not {}",
+            result.type_name()
+        )
+        .into())
+    }
+}
+
+pub fn run_script(
+    script: &str,
+    state: &GameState,
+    ctx: &EffectContext,
+) -> Result<Vec<Effect>, String> {
+    let mut engine = sandboxed_engine();
+    register_queries(&mut engine, state, ctx);
+
+    engine
+        .eval::<Dynamic>(script)
+        .and_then(effects_from_result)
+        .map_err(|e| format!("Script error: {}", e))
+}
+
+// Only catches syntax errors, the rest shows up when the script runs
+pub fn check_script(script: &str) -> Result<(), String> {
+    sandboxed_engine()
+        .compile(script)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_branching_script() {
+        let script = r#"
+            if resource("Coins") >= 2 {
+                [lose_resource("Coins", 2), gain_resource("Meat", 1)]
+            } else {
+                take_damage(1)
+            }
+        "#;
+        let mut gs = GameState::init_state();
+        let ctx = EffectContext::default();
+
+        let effects = run_script(script, &gs, &ctx).unwrap();
+        assert_eq!(
+            effects,
+            vec![Effect::TakeDamage {
+                amount: 1,
+                ship: false
+            }]
+        );
+
+        gs.resources.coins = 2;
+        let effects = run_script(script, &gs, &ctx).unwrap();
+        assert_eq!(effects.len(), 2);
+    }
+
+    #[test]
+    fn test_challenge_script() {
+        let script = r#"
+            challenge("Craft", 6, [heal(1)], [take_damage(crew_count())])
+        "#;
+
+        let effects = run_script(
+            script,
+            &GameState::init_state(),
+            &EffectContext::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            &effects[0],
+            Effect::Challenge(Challenge { amount: 6, failure, .. })
+                if failure == &vec![Effect::TakeDamage {
+                    amount: 8,
+                    ship: false,
+                }]
+        ));
+    }
+
+    #[test]
+    fn test_errors_are_not_panics() {
+        let gs = GameState::init_state();
+        let ctx = EffectContext::default();
+
+        assert!(run_script("gain_resource(\"Gold\", 1)", &gs, &ctx)
+            .is_err());
+        assert!(run_script("take_damage(-1)", &gs, &ctx).is_err());
+        assert!(run_script("crew_damage(99)", &gs, &ctx).is_err());
+        assert!(run_script("42", &gs, &ctx).is_err());
+        assert!(run_script("loop {}", &gs, &ctx).is_err());
+        assert!(run_script("deck_size(\"Hand\")", &gs, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let gs = GameState::init_state();
+        let ctx = EffectContext::default();
+        let long_string = r#"
+            let s = "a";
+            for i in 0..20 { s += s; }
+            [gain_resource("Grain", s.len())]
+        "#;
+        let big_map = r#"
+            let m = #{};
+            for i in 0..2000 { m[`k${i}`] = i; }
+            [gain_resource("Grain", m.len())]
+        "#;
+
+        let err = run_script(long_string, &gs, &ctx).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+        let err = run_script(big_map, &gs, &ctx).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn test_deck_and_phase() {
+        let script = r#"
+            if phase() == "ShipActionPhase" {
+                [gain_resource("Grain", deck_size("event"))]
+            } else {
+                []
+            }
+        "#;
+        let gs = GameState::init_state();
+
+        let effects =
+            run_script(script, &gs, &EffectContext::default())
+                .unwrap();
+        assert_eq!(
+            effects,
+            vec![Effect::GainResource {
+                resource: Resource::Grain,
+                amount: gs.event_card_deck.remaining() as u32,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_script() {
+        assert!(check_script("heal(2)").is_ok());
+        assert!(check_script("heal(2").is_err());
+    }
+}
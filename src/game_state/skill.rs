@@ -1,6 +1,10 @@
-use serde::Serialize;
+use std::str::FromStr;
 
-#[derive(Clone, Serialize, Hash, PartialEq, Eq, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq, Debug,
+)]
 pub enum Skill {
     Savvy,
     Craft,
@@ -17,3 +21,18 @@ impl std::fmt::Display for Skill {
         write!(f, "{:?}", self)
     }
 }
+
+impl FromStr for Skill {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Savvy" => Ok(Skill::Savvy),
+            "Craft" => Ok(Skill::Craft),
+            "Perception" => Ok(Skill::Perception),
+            "Strength" => Ok(Skill::Strength),
+            "Wits" => Ok(Skill::Wits),
+            _ => Err(format!("Unknown skill {}", s)),
+        }
+    }
+}
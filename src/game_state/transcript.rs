@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    action::Action, client_message::ClientMessage, locale::Locales,
+};
+
+// One executed action and everything that came out of it, as kept
+// in the game's action log
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    // The action as the client sent it
+    pub action: Value,
+    pub title: String,
+    // Message keys with their arguments, see ClientMessage
+    pub events: Vec<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        action: &dyn Action,
+        events: &[ClientMessage],
+    ) -> Result<Self, String> {
+        Ok(HistoryEntry {
+            action: serde_json::to_value(action)
+                .map_err(|e| e.to_string())?,
+            title: action.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+        })
+    }
+}
+
+pub fn render_markdown(
+    history: &[HistoryEntry],
+    locales: &Locales,
+    locale: &str,
+) -> String {
+    let mut out =
+        format!("# {}\n", locales.render(locale, "transcript.title"));
+
+    // Actions that had no visible outcome (accepting messages etc.)
+    // are left out
+    for entry in history.iter().filter(|e| !e.events.is_empty()) {
+        let title = entry.title.lines().next().unwrap_or_default();
+        let _ =
+            write!(out, "\n## {}\n\n", title.trim_end_matches(':'));
+
+        for event in entry.events.iter() {
+            let text = locales.render(locale, event);
+            let _ = writeln!(out, "- {}", text);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{
+        content::{Content, DEFAULT_CONTENT_DIR},
+        locale::DEFAULT_LOCALE,
+        skill::Skill,
+        ShipRoom,
+    };
+
+    fn entry(title: &str, events: &[ClientMessage]) -> HistoryEntry {
+        HistoryEntry {
+            action: Value::Null,
+            title: title.to_owned(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let history = vec![
+            entry(
+                "Take Ship Action:\n Room: Bridge",
+                &[
+                    ClientMessage::TookRoom {
+                        room: ShipRoom::Bridge,
+                    },
+                    ClientMessage::GainCommandPoints { amount: 3 },
+                ],
+            ),
+            entry("Accept Message", &[]),
+            entry(
+                "Resolve Challenge Action",
+                &[
+                    ClientMessage::ChallengeResolved {
+                        skill: Skill::Craft,
+                        total: 6,
+                        target: 8,
+                    },
+                    ClientMessage::TookDamage {
+                        crew: "Sofi Odessa".to_owned(),
+                        amount: 5,
+                    },
+                ],
+            ),
+        ];
+
+        let locales =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap().locales;
+        insta::assert_snapshot!(render_markdown(
+            &history,
+            &locales,
+            DEFAULT_LOCALE
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+mod config;
+mod game_state;
+mod server;
+mod store;
+
+pub use game_state::content::{check_content, DEFAULT_CONTENT_DIR};
+pub use server::run_server;
@@ -1,7 +1,4 @@
-mod game_state;
-mod server;
-
-use server::run_server;
+use sleeping_gods::run_server;
 
 fn main() {
     run_server();
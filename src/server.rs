@@ -10,13 +10,33 @@ use websocket::{
     Message, OwnedMessage,
 };
 
-use crate::game_state::{
-    action::{get_action, Action},
-    GameState,
+use crate::{
+    config::{ServerConfig, CONFIG_PATH},
+    game_state::{
+        action::{get_action, Action},
+        campaign::Campaign,
+        client_message::ClientMessage,
+        content::Content,
+        game_setup::GameSetup,
+        locale::{message, Locales, DEFAULT_LOCALE},
+        pack::available_packs,
+        save::{read_save, write_save},
+        transcript::{render_markdown, HistoryEntry},
+        GameState,
+    },
+    store::{check_game_id, open_store, GameStore},
 };
 
+// A new game never takes over the log and save of another one
+fn new_game_id() -> String {
+    format!("game_{:016x}", rand::random::<u64>())
+}
+
 struct GameManager {
     state: GameState,
+    game_id: String,
+    store: Box<dyn GameStore>,
+    content_dir: String,
 }
 
 impl GameManager {
@@ -24,74 +44,227 @@ impl GameManager {
         &mut self,
         action: &dyn Action,
     ) -> Option<String> {
+        if self.state.is_game_over() && !action.after_game_over() {
+            return Some("error.game_over".to_owned());
+        }
         let res = action.execute(&self.state);
 
         match res {
             Ok(gs) => {
                 self.state = gs;
+                let events = self.state.take_events();
+                self.log_action(action, &events);
                 None
             }
             Err(err) => Some(err.to_owned()),
         }
     }
 
-    fn restart(&mut self) {
-        self.state = GameState::init_state();
+    // A failing log shouldn't stop the game, so this only reports
+    fn log_action(
+        &self,
+        action: &dyn Action,
+        events: &[ClientMessage],
+    ) {
+        let result = HistoryEntry::new(action, events)
+            .and_then(|entry| {
+                serde_json::to_value(entry).map_err(|e| e.to_string())
+            })
+            .and_then(|entry| {
+                self.store.append_action(&self.game_id, &entry)
+            });
+
+        if let Err(err) = result {
+            println!("Error logging action: {}", err);
+        }
+    }
+
+    // Any game in the store can be exported, not just this one.
+    // Entries logged before the events were kept are left out.
+    fn read_history(
+        &self,
+        game_id: &str,
+    ) -> Result<Vec<HistoryEntry>, String> {
+        check_game_id(game_id)?;
+        let entries = self.store.read_actions(game_id)?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| serde_json::from_value(entry).ok())
+            .collect())
+    }
+
+    // Returns the string tables of the packs the new game uses
+    fn restart(
+        &mut self,
+        setup: &GameSetup,
+    ) -> Result<Locales, String> {
+        let game_id = match &setup.game_id {
+            Some(game_id) => game_id.clone(),
+            None if setup.from_campaign => self.game_id.clone(),
+            None => new_game_id(),
+        };
+        check_game_id(&game_id)?;
+
+        let seed = setup.seed.unwrap_or_else(rand::random);
+
+        let (state, content) = if setup.from_campaign {
+            let campaign = self.load_campaign(&game_id)?;
+            let content = Content::load(
+                &self.content_dir,
+                &campaign.pack_ids(),
+            )?;
+            content.check_pack_versions(&campaign.packs)?;
+            (
+                GameState::from_campaign(campaign, &content, seed)?,
+                content,
+            )
+        } else {
+            if self.store.load_state(&game_id)?.is_some() {
+                return Err(message(
+                    "error.game_exists",
+                    &[&game_id],
+                ));
+            }
+            let content =
+                Content::load(&self.content_dir, &setup.packs)?;
+            let crew = content.choose_crew(&setup.crew)?;
+            (GameState::new(&content, seed).with_crew(crew), content)
+        };
+        self.state = state;
+        self.game_id = game_id;
+        Ok(content.locales)
+    }
+
+    fn load_campaign(
+        &self,
+        game_id: &str,
+    ) -> Result<Campaign, String> {
+        self.store
+            .load_state(game_id)?
+            .ok_or(message("error.no_campaign", &[&game_id]))
+            .and_then(read_save)
+    }
+
+    fn end_session(&self) -> Result<(), String> {
+        self.state
+            .end_session()
+            .and_then(|campaign| write_save(&campaign))
+            .and_then(|save| {
+                self.store.save_state(&self.game_id, &save)
+            })
+    }
+}
+
+// No setup at all starts a default game, but one that can't be read
+// is reported rather than replaced by the default
+fn read_setup(msg: &Value) -> Result<GameSetup, String> {
+    match msg {
+        Value::Null => Ok(GameSetup::default()),
+        msg => serde_json::from_value(msg.clone())
+            .map_err(|e| message("error.bad_setup", &[&e])),
     }
 }
 
 type Sender = Writer<TcpStream>;
 
+struct Client {
+    sender: Sender,
+    // Text sent to this client is rendered in this locale
+    locale: String,
+}
+
 struct ServerState {
     manager: Mutex<GameManager>,
-    clients: Mutex<HashMap<String, Sender>>,
+    clients: Mutex<HashMap<String, Client>>,
+    locales: Mutex<Locales>,
 }
 
 impl ServerState {
     fn reset(&self) {
         let mut manager = self.manager.lock().unwrap();
-        manager.restart();
+        let _ = manager.restart(&GameSetup::default());
     }
 
-    fn add_client(&self, addr: &str, client: Sender) {
+    fn add_client(&self, addr: &str, sender: Sender) {
         let mut clients = self.clients.lock().unwrap();
 
-        clients.insert(addr.to_owned(), client);
+        clients.insert(
+            addr.to_owned(),
+            Client {
+                sender,
+                locale: DEFAULT_LOCALE.to_owned(),
+            },
+        );
     }
 
-    fn broadcast(&self, msg: &str) {
-        let mut clients = self.clients.lock().unwrap();
+    fn broadcast_gamestate(&self) {
+        let manager = self.manager.lock().unwrap();
+        let state = json!(manager.state);
 
+        let mut clients = self.clients.lock().unwrap();
         for client in clients.values_mut() {
-            let _ = client.send_message(&Message::text(msg));
+            self.send_to(client, "update", state.clone());
         }
     }
 
-    fn broadcast_gamestate(&self) {
-        let manager = self.manager.lock().unwrap();
-        let message = json!({
-            "msgType": "update",
-            "msgData": manager.state,
-        });
+    // The string tables, for the names and other keys in the state
+    fn send_strings(&self, addr: &str) {
+        let locale = self.locale(addr);
+        let strings = self.locales.lock().unwrap().strings(&locale);
+        self.send(addr, "strings", json!(strings));
+    }
 
-        self.broadcast(&message.to_string())
+    fn broadcast_strings(&self) {
+        let clients = self.clients.lock().unwrap();
+        let addrs: Vec<String> = clients.keys().cloned().collect();
+        drop(clients);
+
+        for addr in addrs {
+            self.send_strings(&addr);
+        }
     }
 
-    fn notify(&self, addr: &str, msg: &str) {
+    fn send(&self, addr: &str, msg_type: &str, msg_data: Value) {
         let mut clients = self.clients.lock().unwrap();
         let client = clients.get_mut(addr);
 
         if let Some(client) = client {
-            let message = json!({
-                "msgType": "notify",
-                "msgData": msg
-            });
-            client
-                .send_message(&Message::text(message.to_string()))
-                .unwrap();
+            self.send_to(client, msg_type, msg_data);
         }
     }
 
+    fn send_to(
+        &self,
+        client: &mut Client,
+        msg_type: &str,
+        mut msg_data: Value,
+    ) {
+        let locales = self.locales.lock().unwrap();
+        locales.localize_json(&client.locale, &mut msg_data);
+        drop(locales);
+
+        let message = json!({
+            "msgType": msg_type,
+            "msgData": msg_data
+        });
+        let _ = client
+            .sender
+            .send_message(&Message::text(message.to_string()));
+    }
+
+    fn locale(&self, addr: &str) -> String {
+        let clients = self.clients.lock().unwrap();
+        clients
+            .get(addr)
+            .map(|c| c.locale.clone())
+            .unwrap_or(DEFAULT_LOCALE.to_owned())
+    }
+
+    fn notify(&self, addr: &str, msg: &str) {
+        self.send(addr, "notify", json!(msg));
+    }
+
     fn handle_action_message(&self, addr: &str, msg: &str) {
         let mut manager = self.manager.lock().unwrap();
         let action = get_action(msg);
@@ -103,6 +276,8 @@ impl ServerState {
         }
 
         let result = manager.execute_action(action.as_ref());
+        drop(manager);
+
         match result {
             Some(err) => {
                 println!("Error executing action: {}", err);
@@ -111,17 +286,133 @@ impl ServerState {
             None => {
                 println!("Action {} executed successfully.", action);
 
-                drop(manager);
                 self.broadcast_gamestate();
             }
         }
     }
 
-    fn handle_restart_message(&self) {
+    fn handle_restart_message(&self, addr: &str, msg: &Value) {
         println!("Restarting...");
-        let mut manager = self.manager.lock().unwrap();
-        manager.restart();
+        let result = read_setup(msg).and_then(|setup| {
+            self.manager.lock().unwrap().restart(&setup)
+        });
+
+        match result {
+            Ok(locales) => {
+                *self.locales.lock().unwrap() = locales;
+                self.broadcast_strings();
+                self.broadcast_gamestate()
+            }
+            Err(err) => {
+                println!("Error restarting: {}", err);
+                self.notify(addr, &err);
+            }
+        }
+    }
+
+    fn handle_end_session_message(&self, addr: &str) {
+        println!("Ending session...");
+        let manager = self.manager.lock().unwrap();
+        let result = manager.end_session();
+        drop(manager);
+
+        match result {
+            Ok(()) => self.notify(addr, "info.campaign_saved"),
+            Err(err) => {
+                println!("Error ending session: {}", err);
+                self.notify(addr, &err);
+            }
+        }
+    }
+
+    // Exports the game named in the message, or the one being played
+    fn handle_export_transcript_message(
+        &self,
+        addr: &str,
+        msg: &Value,
+    ) {
+        let manager = self.manager.lock().unwrap();
+        let game_id = msg
+            .get("game_id")
+            .and_then(Value::as_str)
+            .unwrap_or(&manager.game_id)
+            .to_owned();
+        let result = manager.read_history(&game_id);
         drop(manager);
+
+        match result {
+            Ok(history) => {
+                let locale = self.locale(addr);
+                let locales = self.locales.lock().unwrap();
+                let transcript =
+                    render_markdown(&history, &locales, &locale);
+                drop(locales);
+
+                let data = json!({
+                    "game_id": game_id,
+                    "format": "markdown",
+                    "text": transcript,
+                });
+                self.send(addr, "transcript", data);
+            }
+            Err(err) => self.notify(addr, &err),
+        }
+    }
+
+    fn handle_action_log_message(&self, addr: &str) {
+        let manager = self.manager.lock().unwrap();
+        let result = manager.store.read_actions(&manager.game_id);
+        drop(manager);
+
+        match result {
+            Ok(actions) => {
+                self.send(addr, "actionLog", json!(actions))
+            }
+            Err(err) => self.notify(addr, &err),
+        }
+    }
+
+    fn handle_list_games_message(&self, addr: &str) {
+        let manager = self.manager.lock().unwrap();
+        let result = manager.store.list_games();
+        drop(manager);
+
+        match result {
+            Ok(games) => self.send(addr, "games", json!(games)),
+            Err(err) => self.notify(addr, &err),
+        }
+    }
+
+    fn handle_list_packs_message(&self, addr: &str) {
+        let manager = self.manager.lock().unwrap();
+        let result = available_packs(&manager.content_dir);
+        drop(manager);
+
+        match result {
+            Ok(packs) => self.send(addr, "packs", json!(packs)),
+            Err(err) => self.notify(addr, &err),
+        }
+    }
+
+    fn handle_set_locale_message(&self, addr: &str, msg: &Value) {
+        let locale = msg.as_str().unwrap_or_default();
+        let known = self.locales.lock().unwrap().has_locale(locale);
+        if !known {
+            self.notify(
+                addr,
+                &message("error.unknown_locale", &[&locale]),
+            );
+            return;
+        }
+
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get_mut(addr) {
+            client.locale = locale.to_owned();
+        }
+        drop(clients);
+
+        self.send_strings(addr);
+
         self.broadcast_gamestate();
     }
 
@@ -141,7 +432,28 @@ impl ServerState {
                         addr,
                         &msg_data.to_string(),
                     ),
-                    "restart" => self.handle_restart_message(),
+                    "restart" => {
+                        self.handle_restart_message(addr, msg_data)
+                    }
+                    "endSession" => {
+                        self.handle_end_session_message(addr)
+                    }
+                    "exportTranscript" => self
+                        .handle_export_transcript_message(
+                            addr, msg_data,
+                        ),
+                    "listGames" => {
+                        self.handle_list_games_message(addr)
+                    }
+                    "actionLog" => {
+                        self.handle_action_log_message(addr)
+                    }
+                    "listPacks" => {
+                        self.handle_list_packs_message(addr)
+                    }
+                    "setLocale" => {
+                        self.handle_set_locale_message(addr, msg_data)
+                    }
                     _ => (),
                 }
             }
@@ -150,13 +462,27 @@ impl ServerState {
 }
 
 pub fn run_server() {
+    let config = ServerConfig::load(CONFIG_PATH).unwrap();
+    let store = open_store(&config.store).unwrap();
+
+    // Bad content should stop the server before anyone connects
+    let content = match Content::load(&config.content_dir, &[]) {
+        Ok(content) => content,
+        Err(err) => panic!("Invalid content:\n{}", err),
+    };
+
+    let locales = content.locales.clone();
     let state = Arc::new(ServerState {
         manager: Mutex::new(GameManager {
-            state: GameState::init_state(),
+            state: GameState::new(&content, rand::random()),
+            game_id: new_game_id(),
+            store,
+            content_dir: config.content_dir.clone(),
         }),
         clients: Mutex::new(HashMap::new()),
+        locales: Mutex::new(locales),
     });
-    let wsserver = Server::bind("localhost:2000").unwrap();
+    let wsserver = Server::bind(&config.address).unwrap();
 
     for connection in wsserver.filter_map(Result::ok) {
         let state = Arc::clone(&state);
@@ -172,6 +498,7 @@ pub fn run_server() {
             let (mut reciever, sender) = client.split().unwrap();
             state.add_client(&client_addr, sender);
 
+            state.send_strings(&client_addr);
             state.broadcast_gamestate();
 
             for message in reciever.incoming_messages() {
@@ -191,3 +518,163 @@ pub fn run_server() {
         });
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        game_state::content::DEFAULT_CONTENT_DIR, store::FsStore,
+    };
+
+    static MANAGER_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn manager() -> GameManager {
+        let dir = std::env::temp_dir().join(format!(
+            "sleeping_gods_server_{}_{}",
+            std::process::id(),
+            MANAGER_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let content =
+            Content::load(DEFAULT_CONTENT_DIR, &[]).unwrap();
+
+        GameManager {
+            state: GameState::new(&content, 0),
+            game_id: new_game_id(),
+            store: Box::new(
+                FsStore::new(dir.to_str().unwrap()).unwrap(),
+            ),
+            content_dir: DEFAULT_CONTENT_DIR.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_new_game_gets_fresh_id() {
+        let mut manager = manager();
+        let first = manager.game_id.clone();
+
+        manager.restart(&GameSetup::default()).unwrap();
+        assert_ne!(manager.game_id, first);
+    }
+
+    #[test]
+    fn test_resume_keeps_id() {
+        let mut manager = manager();
+        let game_id = manager.game_id.clone();
+        manager.end_session().unwrap();
+
+        let setup = GameSetup {
+            from_campaign: true,
+            ..GameSetup::default()
+        };
+        manager.restart(&setup).unwrap();
+        assert_eq!(manager.game_id, game_id);
+    }
+
+    #[test]
+    fn test_err_if_pack_version_changed() {
+        let mut manager = manager();
+        manager.end_session().unwrap();
+        let mut save = manager
+            .store
+            .load_state(&manager.game_id)
+            .unwrap()
+            .unwrap();
+        save["campaign"]["packs"][0]["version"] = json!("0.0.1");
+        manager.store.save_state(&manager.game_id, &save).unwrap();
+
+        let setup = GameSetup {
+            from_campaign: true,
+            ..GameSetup::default()
+        };
+        assert!(manager.restart(&setup).is_err());
+    }
+
+    #[test]
+    fn test_err_if_new_game_would_overwrite_save() {
+        let mut manager = manager();
+        manager.store.save_state("taken", &json!({})).unwrap();
+
+        let setup = GameSetup {
+            game_id: Some("taken".to_owned()),
+            ..GameSetup::default()
+        };
+        assert!(manager.restart(&setup).is_err());
+    }
+
+    #[test]
+    fn test_only_messages_after_game_over() {
+        let mut manager = manager();
+        manager.state = manager.state.clone().lose_game();
+        let queued = |m: &GameManager| {
+            json!(m.state)["message_queue"].as_array().unwrap().len()
+        };
+        assert_eq!(queued(&manager), 1);
+
+        let end_turn = get_action(
+            r#"{ "actionType": "endTurnAction",
+                 "actionData": { "player_ix": 0 } }"#,
+        );
+        assert_eq!(
+            manager.execute_action(end_turn.as_ref()),
+            Some("error.game_over".to_owned())
+        );
+
+        let accept = get_action(
+            r#"{ "actionType": "acceptMessageAction",
+                 "actionData": { "player_ix": 0 } }"#,
+        );
+        assert_eq!(manager.execute_action(accept.as_ref()), None);
+        assert_eq!(queued(&manager), 0);
+        assert!(manager.state.is_game_over());
+    }
+
+    #[test]
+    fn test_history_from_action_log() {
+        let mut manager = manager();
+        let game_id = manager.game_id.clone();
+        let take_bridge = get_action(
+            r#"{ "actionType": "takeShipAction",
+                 "actionData": { "room": "Bridge", "player_ix": 0 } }"#,
+        );
+        assert_eq!(
+            manager.execute_action(take_bridge.as_ref()),
+            None
+        );
+        manager.restart(&GameSetup::default()).unwrap();
+
+        let history = manager.read_history(&game_id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action["actionType"], "takeShipAction");
+        assert!(!history[0].events.is_empty());
+
+        assert!(manager
+            .read_history(&manager.game_id)
+            .unwrap()
+            .is_empty());
+        assert!(manager.read_history("../escape").is_err());
+    }
+
+    #[test]
+    fn test_read_setup() {
+        assert!(read_setup(&Value::Null).is_ok());
+        assert!(read_setup(&json!({ "packs": ["islands"] })).is_ok());
+        assert!(read_setup(&json!({ "packs": "islands" })).is_err());
+        assert!(
+            read_setup(&json!({ "from_campaign": "yes" })).is_err()
+        );
+    }
+
+    #[test]
+    fn test_err_if_game_id_invalid() {
+        let mut manager = manager();
+        let setup = GameSetup {
+            game_id: Some("../escape".to_owned()),
+            ..GameSetup::default()
+        };
+
+        assert!(manager.restart(&setup).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::game_state::locale::message;
+
+mod fs_store;
+#[cfg(feature = "sqlite")]
+mod sqlite_store;
+
+pub use fs_store::FsStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite_store::SqliteStore;
+
+// Where saved games and their action logs are kept. States and
+// actions are stored as JSON so the backends don't need to know
+// about the game types.
+pub trait GameStore: Send {
+    fn save_state(
+        &self,
+        game_id: &str,
+        state: &Value,
+    ) -> StoreResult<()>;
+    fn load_state(&self, game_id: &str)
+        -> StoreResult<Option<Value>>;
+    fn append_action(
+        &self,
+        game_id: &str,
+        action: &Value,
+    ) -> StoreResult<()>;
+    fn read_actions(&self, game_id: &str) -> StoreResult<Vec<Value>>;
+    fn list_games(&self) -> StoreResult<Vec<String>>;
+}
+
+pub type StoreResult<T> = Result<T, String>;
+
+// Ids end up in file names, so callers check them before they
+// reach a store
+pub fn check_game_id(game_id: &str) -> StoreResult<()> {
+    let valid = !game_id.is_empty()
+        && game_id.chars().all(|c| {
+            c.is_ascii_alphanumeric() || c == '-' || c == '_'
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(message("error.bad_game_id", &[&game_id]))
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StoreConfig {
+    Filesystem {
+        path: String,
+    },
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+    Sqlite {
+        path: String,
+    },
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig::Filesystem {
+            path: "saves".to_owned(),
+        }
+    }
+}
+
+pub fn open_store(
+    config: &StoreConfig,
+) -> StoreResult<Box<dyn GameStore>> {
+    match config {
+        StoreConfig::Filesystem { path } => {
+            Ok(Box::new(FsStore::new(path)?))
+        }
+        #[cfg(feature = "sqlite")]
+        StoreConfig::Sqlite { path } => {
+            Ok(Box::new(SqliteStore::open(path)?))
+        }
+        #[cfg(not(feature = "sqlite"))]
+        StoreConfig::Sqlite { .. } => {
+            Err("The server was built without the sqlite feature"
+                .to_owned())
+        }
+    }
+}
+
+// Every backend runs the same tests, see the `test` module of each
+#[cfg(test)]
+macro_rules! store_test_suite {
+    ($make_store:expr) => {
+        use serde_json::json;
+
+        use crate::store::GameStore;
+
+        #[test]
+        fn test_load_missing_state() {
+            let store = $make_store;
+            assert_eq!(store.load_state("nothing").unwrap(), None);
+        }
+
+        #[test]
+        fn test_save_and_load_state() {
+            let store = $make_store;
+            let state = json!({ "crew": [], "ship_area": 1 });
+
+            store.save_state("game", &state).unwrap();
+            assert_eq!(store.load_state("game").unwrap(), Some(state));
+        }
+
+        #[test]
+        fn test_save_overwrites_state() {
+            let store = $make_store;
+
+            store.save_state("game", &json!({ "turn": 1 })).unwrap();
+            store.save_state("game", &json!({ "turn": 2 })).unwrap();
+
+            assert_eq!(
+                store.load_state("game").unwrap(),
+                Some(json!({ "turn": 2 }))
+            );
+        }
+
+        #[test]
+        fn test_action_log_keeps_order() {
+            let store = $make_store;
+            let actions: Vec<_> = (0..5)
+                .map(|ix| json!({ "actionType": "noAction", "ix": ix }))
+                .collect();
+
+            for action in actions.iter() {
+                store.append_action("game", action).unwrap();
+            }
+            store.append_action("other", &json!({})).unwrap();
+
+            assert_eq!(store.read_actions("game").unwrap(), actions);
+            assert!(store.read_actions("missing").unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_list_games() {
+            let store = $make_store;
+            assert!(store.list_games().unwrap().is_empty());
+
+            store.save_state("b", &json!({})).unwrap();
+            store.append_action("a", &json!({})).unwrap();
+            store.save_state("a", &json!({})).unwrap();
+
+            assert_eq!(store.list_games().unwrap(), vec!["a", "b"]);
+        }
+    };
+}
+#[cfg(test)]
+use store_test_suite;
+
+#[cfg(test)]
+mod test {
+    use super::{check_game_id, message};
+
+    #[test]
+    fn test_rejects_path_in_game_id() {
+        assert_eq!(
+            check_game_id("../escape"),
+            Err(message("error.bad_game_id", &[&"../escape"]))
+        );
+        assert!(check_game_id("").is_err());
+        assert!(check_game_id("game_1-a").is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use serde_json::Value;
+
+use super::{GameStore, StoreResult};
+
+const STATE_FILE: &str = "state.json";
+const ACTIONS_FILE: &str = "actions.jsonl";
+
+// Keeps one directory per game, holding the latest state and the
+// action log with one action per line
+pub struct FsStore {
+    root: PathBuf,
+}
+
+impl FsStore {
+    pub fn new(root: &str) -> StoreResult<Self> {
+        fs::create_dir_all(root).map_err(|e| e.to_string())?;
+        Ok(FsStore { root: root.into() })
+    }
+
+    // The id is checked by the caller, see check_game_id
+    fn game_dir(&self, game_id: &str) -> PathBuf {
+        self.root.join(game_id)
+    }
+}
+
+impl GameStore for FsStore {
+    fn save_state(
+        &self,
+        game_id: &str,
+        state: &Value,
+    ) -> StoreResult<()> {
+        let dir = self.game_dir(game_id);
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+        let json = serde_json::to_string_pretty(state)
+            .map_err(|e| e.to_string())?;
+
+        // Write to a temporary file first so a crash can't leave a
+        // half written save behind
+        let tmp = dir.join(format!("{}.tmp", STATE_FILE));
+        fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, dir.join(STATE_FILE))
+            .map_err(|e| e.to_string())
+    }
+
+    fn load_state(
+        &self,
+        game_id: &str,
+    ) -> StoreResult<Option<Value>> {
+        let path = self.game_dir(game_id).join(STATE_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json =
+            fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    fn append_action(
+        &self,
+        game_id: &str,
+        action: &Value,
+    ) -> StoreResult<()> {
+        let dir = self.game_dir(game_id);
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(ACTIONS_FILE))
+            .map_err(|e| e.to_string())?;
+
+        writeln!(file, "{}", action).map_err(|e| e.to_string())
+    }
+
+    fn read_actions(&self, game_id: &str) -> StoreResult<Vec<Value>> {
+        let path = self.game_dir(game_id).join(ACTIONS_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    fn list_games(&self) -> StoreResult<Vec<String>> {
+        let mut games: Vec<String> = fs::read_dir(&self.root)
+            .map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+
+        games.sort();
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::FsStore;
+
+    static STORE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_store() -> FsStore {
+        let dir = std::env::temp_dir().join(format!(
+            "sleeping_gods_store_{}_{}",
+            std::process::id(),
+            STORE_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        FsStore::new(dir.to_str().unwrap()).unwrap()
+    }
+
+    crate::store::store_test_suite!(temp_store());
+}
@@ -0,0 +1,133 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use super::{GameStore, StoreResult};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS states (
+        game_id TEXT PRIMARY KEY,
+        state TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS actions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        game_id TEXT NOT NULL,
+        action TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS actions_by_game ON actions (game_id, id);
+";
+
+// Keeps every game in a single embedded database file
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: &str) -> StoreResult<Self> {
+        Connection::open(path)
+            .map_err(|e| e.to_string())
+            .and_then(Self::init)
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> StoreResult<Self> {
+        Connection::open_in_memory()
+            .map_err(|e| e.to_string())
+            .and_then(Self::init)
+    }
+
+    fn init(conn: Connection) -> StoreResult<Self> {
+        conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+        Ok(SqliteStore { conn })
+    }
+}
+
+fn parse(json: String) -> StoreResult<Value> {
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+impl GameStore for SqliteStore {
+    fn save_state(
+        &self,
+        game_id: &str,
+        state: &Value,
+    ) -> StoreResult<()> {
+        self.conn
+            .execute(
+                "INSERT INTO states (game_id, state) VALUES (?1, ?2)
+                 ON CONFLICT (game_id) DO UPDATE SET state = excluded.state",
+                params![game_id, state.to_string()],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn load_state(
+        &self,
+        game_id: &str,
+    ) -> StoreResult<Option<Value>> {
+        self.conn
+            .query_row(
+                "SELECT state FROM states WHERE game_id = ?1",
+                params![game_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .map(parse)
+            .transpose()
+    }
+
+    fn append_action(
+        &self,
+        game_id: &str,
+        action: &Value,
+    ) -> StoreResult<()> {
+        self.conn
+            .execute(
+                "INSERT INTO actions (game_id, action) VALUES (?1, ?2)",
+                params![game_id, action.to_string()],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn read_actions(&self, game_id: &str) -> StoreResult<Vec<Value>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT action FROM actions WHERE game_id = ?1 ORDER BY id")
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map(params![game_id], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.map(|row| row.map_err(|e| e.to_string()).and_then(parse))
+            .collect()
+    }
+
+    fn list_games(&self) -> StoreResult<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT game_id FROM states
+                 UNION SELECT game_id FROM actions
+                 ORDER BY game_id",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+
+        rows.map(|row| row.map_err(|e| e.to_string())).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SqliteStore;
+
+    crate::store::store_test_suite!(SqliteStore::in_memory().unwrap());
+}